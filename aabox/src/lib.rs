@@ -0,0 +1,245 @@
+use std::ops::RangeInclusive;
+
+pub type Range = RangeInclusive<i32>;
+
+/// Axis aligned box in `N` dimensions, every range includes both of its ends.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct AaBox<const N: usize> {
+    ranges: [Range; N],
+}
+
+impl<const N: usize> AaBox<N> {
+    pub fn new(ranges: [Range; N]) -> AaBox<N> {
+        AaBox { ranges }
+    }
+
+    /// parses `x=a..b,y=c..d`, the axis names are optional and spaces after commas are allowed
+    pub fn parse(input: &str) -> Result<AaBox<N>, Box<dyn std::error::Error>> {
+        let ranges = input
+            .trim()
+            .split(',')
+            .map(|part| {
+                let part = part.trim();
+                Self::parse_range(part.split_once('=').map_or(part, |(_, range)| range))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let amount = ranges.len();
+        let ranges = ranges
+            .try_into()
+            .map_err(|_| format!("expected {} ranges, found {}", N, amount))?;
+        Ok(AaBox::new(ranges))
+    }
+
+    fn parse_range(input: &str) -> Result<Range, Box<dyn std::error::Error>> {
+        let (start, end) = input.split_once("..").ok_or("invalid range")?;
+        let start: i32 = start.parse()?;
+        let end: i32 = end.parse()?;
+        if start > end {
+            return Err(format!("empty range {}..{}", start, end).into());
+        }
+        Ok(start..=end)
+    }
+
+    pub fn ranges(&self) -> &[Range; N] {
+        &self.ranges
+    }
+
+    pub fn range(&self, axis: usize) -> &Range {
+        &self.ranges[axis]
+    }
+
+    /// lowest corner
+    pub fn min(&self) -> [i32; N] {
+        std::array::from_fn(|axis| *self.ranges[axis].start())
+    }
+
+    /// highest corner
+    pub fn max(&self) -> [i32; N] {
+        std::array::from_fn(|axis| *self.ranges[axis].end())
+    }
+
+    pub fn contains(&self, point: &[i32; N]) -> bool {
+        self.ranges
+            .iter()
+            .zip(point.iter())
+            .all(|(range, value)| range.contains(value))
+    }
+
+    pub fn intersect(&self, other: &AaBox<N>) -> Option<AaBox<N>> {
+        let mut ranges = self.ranges.clone();
+        for (axis, range) in ranges.iter_mut().enumerate() {
+            let start = *self.ranges[axis].start().max(other.ranges[axis].start());
+            let end = *self.ranges[axis].end().min(other.ranges[axis].end());
+            if start > end {
+                return None;
+            }
+            *range = start..=end;
+        }
+        Some(AaBox::new(ranges))
+    }
+
+    /// the parts of this box not covered by `other`, as at most `2 * N` disjoint boxes
+    pub fn subtract(&self, other: &AaBox<N>) -> Vec<AaBox<N>> {
+        let overlap = match self.intersect(other) {
+            Some(overlap) => overlap,
+            None => return vec![self.clone()],
+        };
+
+        let mut pieces = Vec::new();
+        let mut rest = self.clone();
+        // slice off the parts before and after the overlap, one axis at a time
+        for axis in 0..N {
+            let (start, end) = (*rest.ranges[axis].start(), *rest.ranges[axis].end());
+            let (overlap_start, overlap_end) =
+                (*overlap.ranges[axis].start(), *overlap.ranges[axis].end());
+            if start < overlap_start {
+                let mut piece = rest.clone();
+                piece.ranges[axis] = start..=overlap_start - 1;
+                pieces.push(piece);
+            }
+            if overlap_end < end {
+                let mut piece = rest.clone();
+                piece.ranges[axis] = overlap_end + 1..=end;
+                pieces.push(piece);
+            }
+            rest.ranges[axis] = overlap_start..=overlap_end;
+        }
+        pieces
+    }
+
+    /// Amount of points inside, `None` when it does not fit in `i128`. That never happens up to
+    /// 3 dimensions, from 4 on it can for wide ranges.
+    pub fn volume(&self) -> Option<i128> {
+        self.ranges.iter().try_fold(1i128, |volume, range| {
+            volume.checked_mul(*range.end() as i128 - *range.start() as i128 + 1)
+        })
+    }
+
+    /// amount of points inside at least one of the boxes, `None` when it does not fit in `i128`
+    pub fn union_volume(boxes: &[AaBox<N>]) -> Option<i128> {
+        let mut disjoint: Vec<AaBox<N>> = Vec::new();
+        for aabox in boxes {
+            disjoint = disjoint.iter().flat_map(|x| x.subtract(aabox)).collect();
+            disjoint.push(aabox.clone());
+        }
+        disjoint
+            .iter()
+            .try_fold(0i128, |total, x| total.checked_add(x.volume()?))
+    }
+}
+
+#[cfg(test)]
+mod aabox_tests {
+    use super::AaBox;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            AaBox::new([10..=12, -3..=4, 0..=0]),
+            AaBox::<3>::parse("x=10..12,y=-3..4,z=0..0").unwrap()
+        );
+        assert_eq!(
+            AaBox::new([20..=30, -10..=-5]),
+            AaBox::<2>::parse("x=20..30, y=-10..-5\n").unwrap()
+        );
+        assert_eq!(AaBox::new([1..=2]), AaBox::<1>::parse("1..2").unwrap());
+
+        assert!(AaBox::<3>::parse("x=10..12,y=-3..4").is_err());
+        assert!(AaBox::<2>::parse("x=10..12,y=-3").is_err());
+        assert!(AaBox::<2>::parse("x=10..12,y=a..4").is_err());
+        assert!(AaBox::<1>::parse("x=4..3").is_err());
+    }
+
+    #[test]
+    fn contains() {
+        let aabox = AaBox::new([0..=3, 8..=11]);
+
+        assert!(aabox.contains(&[1, 11]));
+        assert!(aabox.contains(&[0, 8]));
+        assert!(!aabox.contains(&[4, 8]));
+        assert!(!aabox.contains(&[2, 19]));
+    }
+
+    #[test]
+    fn intersect() {
+        let a = AaBox::new([10..=12, 10..=12, 10..=12]);
+        let b = AaBox::new([11..=13, 11..=13, 11..=13]);
+
+        assert_eq!(
+            Some(AaBox::new([11..=12, 11..=12, 11..=12])),
+            a.intersect(&b)
+        );
+        assert_eq!(None, a.intersect(&AaBox::new([13..=14, 0..=20, 0..=20])));
+    }
+
+    #[test]
+    fn subtract() {
+        let a = AaBox::new([10..=12, 10..=12, 10..=12]);
+        let b = AaBox::new([11..=13, 11..=13, 11..=13]);
+
+        let pieces = a.subtract(&b);
+        assert_eq!(3, pieces.len());
+        assert_eq!(
+            27 - 8,
+            pieces.iter().map(|x| x.volume().unwrap()).sum::<i128>()
+        );
+        assert!(pieces.iter().all(|x| x.intersect(&b).is_none()));
+
+        let inner = AaBox::new([11..=11, 11..=11, 11..=11]);
+        assert_eq!(6, a.subtract(&inner).len());
+        assert!(inner.subtract(&a).is_empty());
+
+        let square = AaBox::new([0..=4, 0..=4]);
+        assert_eq!(4, square.subtract(&AaBox::new([2..=2, 2..=2])).len());
+    }
+
+    #[test]
+    fn volume() {
+        assert_eq!(
+            Some(3 * 4 * 4),
+            AaBox::new([10..=12, 10..=13, 11..=14]).volume()
+        );
+        assert_eq!(Some(4), AaBox::new([-10..=-7]).volume());
+        let widest = i32::MIN..=i32::MAX;
+        assert_eq!(
+            Some((1i128 << 32).pow(3)),
+            AaBox::new([widest.clone(), widest.clone(), widest.clone()]).volume()
+        );
+        // 2^128 points
+        assert_eq!(
+            None,
+            AaBox::new([
+                widest.clone(),
+                widest.clone(),
+                widest.clone(),
+                widest.clone()
+            ])
+            .volume()
+        );
+        assert_eq!(
+            None,
+            AaBox::union_volume(&[
+                AaBox::new([
+                    widest.clone(),
+                    widest.clone(),
+                    widest.clone(),
+                    widest.clone()
+                ]),
+                AaBox::new([0..=0, 0..=0, 0..=0, 0..=0]),
+            ])
+        );
+    }
+
+    #[test]
+    fn union_volume() {
+        let boxes = [
+            AaBox::new([10..=12, 10..=12, 10..=12]),
+            AaBox::new([11..=13, 11..=13, 11..=13]),
+            AaBox::new([11..=11, 11..=11, 11..=11]),
+        ];
+
+        assert_eq!(Some(27 + 27 - 8), AaBox::union_volume(&boxes));
+        assert_eq!(Some(0), AaBox::<2>::union_volume(&[]));
+    }
+}
@@ -3,11 +3,18 @@ use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
 use derive_more::{Deref, DerefMut};
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
+pub mod movement;
 pub mod naive_finder;
+pub mod render;
+pub mod tiled_map;
 
-#[derive(Debug, Default, PartialEq, Deref, DerefMut)]
+use movement::Movement;
+use tiled_map::TiledMap;
+
+#[derive(Debug, Default, Clone, PartialEq, Deref, DerefMut)]
 pub struct Map {
     data: Vec<Vec<u8>>,
 }
@@ -25,7 +32,11 @@ impl Map {
             let line = line?;
             let mut row = Vec::with_capacity(line.len());
             for point in line.chars() {
-                row.push(point.to_digit(10).ok_or("invalid digit")? as u8);
+                // risks start at 1, the tiles wrap from 9 back to 1
+                match point.to_digit(10) {
+                    Some(digit @ 1..=9) => row.push(digit as u8),
+                    _ => return Err(format!("invalid risk level {:?}", point).into()),
+                }
             }
             data.push(row)
         }
@@ -36,6 +47,11 @@ impl Map {
         self.data.get(x).map(|row| row.get(y)).flatten()
     }
 
+    /// risk at a `(column, row)` position, the way `Graph` and `Route` store positions
+    pub fn risk(&self, (x, y): &Pair) -> Option<u8> {
+        self.data.get(*y)?.get(*x).copied()
+    }
+
     pub fn endpoint(&self) -> (usize, usize) {
         (self.max_x() - 1, self.max_y() - 1)
     }
@@ -100,14 +116,14 @@ impl Route {
     }
 
     pub fn next_steps(&self) -> Vec<(usize, usize)> {
-        let mut places = Vec::new();
-        if let Some((x, y)) = self.path.last().copied() {
-            let _left = x.checked_sub(1).map(|x| places.push((x, y)));
-            let _right = x.checked_add(1).map(|x| places.push((x, y)));
-            let _up = y.checked_sub(1).map(|y| places.push((x, y)));
-            let _down = y.checked_add(1).map(|y| places.push((x, y)));
+        self.next_steps_with(&Movement::default())
+    }
+
+    pub fn next_steps_with(&self, movement: &Movement) -> Vec<(usize, usize)> {
+        match self.path.last() {
+            Some(position) => movement.moves(position).map(|x| x.1).collect(),
+            None => Vec::new(),
         }
-        places
     }
 
     pub fn contains(&self, coordinate: &(usize, usize)) -> bool {
@@ -117,59 +133,142 @@ impl Route {
     pub fn last(&self) -> Option<&(usize, usize)> {
         self.path.last()
     }
+
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    pub fn path(&self) -> &[(usize, usize)] {
+        &self.path
+    }
 }
 
 type Pair = (usize, usize);
 
+/// Row-major layout shared by the flat graph storage.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Dimensions {
+    width: usize,
+    height: usize,
+}
+
+impl Dimensions {
+    pub fn new(width: usize, height: usize) -> Dimensions {
+        Dimensions { width, height }
+    }
+
+    pub fn size(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn index(&self, (x, y): &Pair) -> Option<usize> {
+        if *x < self.width && *y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+}
+
+/// Edges of the grid, the cost of an edge is the risk of the cell it enters.
 #[derive(Debug, Default)]
 pub struct Edges {
-    data: BTreeMap<Pair, BTreeMap<Pair, u8>>,
+    dimensions: Dimensions,
+    tiles: TiledMap,
+    movement: Movement,
 }
 
 impl Edges {
-    pub fn set(&mut self, from: Pair, to: Pair, cost: u8) {
-        let x = self.data.entry(from).or_insert(BTreeMap::default());
-        *x.entry(to).or_insert(0) = cost;
+    pub fn from_tiled(tiles: TiledMap, movement: Movement) -> Edges {
+        Edges {
+            dimensions: Dimensions::new(tiles.max_x(), tiles.max_y()),
+            tiles,
+            movement,
+        }
+    }
+
+    pub fn get(&self, from: &Pair, to: &Pair) -> Option<u8> {
+        self.connections(from)?.find(|(pair, _)| pair == to)?;
+        self.tiles.risk(to)
     }
 
-    pub fn get(&self, from: &Pair, to: &Pair) -> Option<&u8> {
-        self.data.get(from).map(|inner| inner.get(to)).flatten()
+    pub fn connections<'a>(&'a self, from: &Pair) -> Option<impl Iterator<Item = (Pair, u8)> + 'a> {
+        Some(self.moves(from)?.map(|(_, to, cost)| (to, cost)))
     }
 
-    pub fn connections(&self, from: &Pair) -> Option<&BTreeMap<Pair, u8>> {
-        self.data.get(from)
+    /// connections together with the direction index of the `Movement` used
+    pub fn moves<'a>(
+        &'a self,
+        from: &Pair,
+    ) -> Option<impl Iterator<Item = (usize, Pair, u8)> + 'a> {
+        self.dimensions.index(from)?;
+        Some(
+            self.movement
+                .moves(from)
+                .filter_map(move |(direction, to)| Some((direction, to, self.tiles.risk(&to)?))),
+        )
+    }
+}
+
+/// score of a position or state that was never reached
+const UNREACHED: usize = usize::MAX;
+/// search byte of the start, it was not entered from anywhere
+const START: u8 = 0xF;
+
+/// Best known score per position.
+#[derive(Debug, Default)]
+pub struct Nodes {
+    dimensions: Dimensions,
+    scores: Vec<usize>,
+}
+
+impl Nodes {
+    pub fn new(dimensions: Dimensions) -> Nodes {
+        Nodes {
+            dimensions,
+            scores: vec![UNREACHED; dimensions.size()],
+        }
+    }
+
+    /// `None` outside of the grid, `Some(None)` for a position without a score
+    pub fn get(&self, position: &Pair) -> Option<Option<usize>> {
+        let score = *self.scores.get(self.dimensions.index(position)?)?;
+        Some((score != UNREACHED).then_some(score))
+    }
+
+    pub fn insert(&mut self, position: Pair, score: Option<usize>) {
+        let index = self.dimensions.index(&position).expect("node should exist");
+        self.scores[index] = score.unwrap_or(UNREACHED);
+    }
+
+    /// keeps the lowest of the known score and `score`
+    pub fn lower(&mut self, position: Pair, score: usize) {
+        let index = self.dimensions.index(&position).expect("node should exist");
+        self.scores[index] = score.min(self.scores[index]);
     }
 }
 
 #[derive(Debug, Default)]
 pub struct Graph {
-    nodes: BTreeMap<Pair, Option<usize>>,
+    nodes: Nodes,
     edges: Edges,
     endpoint: Pair,
 }
 
 impl Graph {
     pub fn from_map(map: &Map) -> Graph {
-        let mut nodes = BTreeMap::new();
-        let mut edges = Edges::default();
-        for (y, line) in map.iter().enumerate() {
-            for (x, cost) in line.iter().enumerate() {
-                let current_pos = (x, y);
-                nodes.insert(current_pos.clone(), None);
-                let mut connections = Vec::new();
-                let _left = x.checked_sub(1).map(|x| connections.push((x, y)));
-                let _right = x.checked_add(1).map(|x| connections.push((x, y)));
-                let _up = y.checked_sub(1).map(|y| connections.push((x, y)));
-                let _down = y.checked_add(1).map(|y| connections.push((x, y)));
-                for connection in connections {
-                    edges.set(connection, current_pos.clone(), *cost)
-                }
-            }
-        }
+        Graph::from_map_with(map, Movement::default())
+    }
+
+    pub fn from_map_with(map: &Map, movement: Movement) -> Graph {
+        Graph::from_tiled(TiledMap::new(map, 1), movement)
+    }
+
+    pub fn from_tiled(tiles: TiledMap, movement: Movement) -> Graph {
         Graph {
-            nodes,
-            edges,
-            endpoint: map.endpoint(),
+            nodes: Nodes::new(Dimensions::new(tiles.max_x(), tiles.max_y())),
+            endpoint: tiles.endpoint(),
+            edges: Edges::from_tiled(tiles, movement),
         }
     }
 
@@ -184,20 +283,9 @@ impl Graph {
                     let current_pos = (i, j);
                     if let Some(connections) = self.edges.connections(&current_pos) {
                         for (connection, cost) in connections {
-                            let current_cost = self
-                                .nodes
-                                .get(&current_pos)
-                                .unwrap_or(&None)
-                                .unwrap_or(100000);
-                            let new_cost = current_cost + *cost as usize;
-                            match self.nodes.get_mut(&connection).expect("node should exist") {
-                                Some(point) => {
-                                    if *point > new_cost {
-                                        *point = new_cost
-                                    }
-                                }
-                                point => *point = Some(new_cost),
-                            };
+                            let current_cost =
+                                self.nodes.get(&current_pos).flatten().unwrap_or(100000);
+                            self.nodes.lower(connection, current_cost + cost as usize);
                         }
                     }
                 }
@@ -205,47 +293,90 @@ impl Graph {
         }
     }
 
-    #[allow(unreachable_code)]
-    pub fn start_smart(&mut self) {
-        // incomplete
-
-        return;
-        let mut current_pos = (0, 0);
-        loop {
-            if let Some(connections) = self.edges.connections(&current_pos) {
-                let mut next_pos = None;
-                for (connection, cost) in connections {
-                    let current_cost = self
-                        .nodes
-                        .get(&current_pos)
-                        .unwrap_or(&None)
-                        .unwrap_or(100000);
-                    println!("{:?}", cost);
-                    println!("{:?}", connection);
-                    let new_cost = current_cost + *cost as usize;
-                    match self.nodes.get_mut(&connection).expect("node should exist") {
-                        Some(x) => {
-                            if *x > new_cost {
-                                *x = new_cost
-                            }
-                        }
-                        y => *y = Some(new_cost),
-                    };
-                    next_pos = Some(connection);
-                }
+    /// dijkstra using a binary heap, fills in the nodes it settles
+    pub fn start_dijkstra(&mut self) -> Option<Route> {
+        self.search(|_| 0)
+    }
 
-                if Some(&self.endpoint) == next_pos {
-                    break;
+    /// a* with the distance in moves to the endpoint, finds the cheapest route as long as every
+    /// move costs at least 1
+    pub fn start_a_star(&mut self) -> Option<Route> {
+        let endpoint = self.endpoint;
+        let movement = self.edges.movement.clone();
+        self.search(move |position| movement.distance(position, &endpoint))
+    }
+
+    /// searches over positions, or over positions and the direction they were entered
+    /// from when turning costs extra
+    fn search<F: Fn(&Pair) -> usize>(&mut self, heuristic: F) -> Option<Route> {
+        let dimensions = self.edges.dimensions;
+        let directions = if self.edges.movement.has_move_costs() {
+            self.edges.movement.offsets().len() + 1
+        } else {
+            1
+        };
+        let state = |position: &Pair, direction: Option<usize>| {
+            let slot = direction.map_or(directions - 1, |x| x % directions);
+            Some(dimensions.index(position)? * directions + slot)
+        };
+
+        // besides the best cost a single byte per state holds the direction it was entered
+        // with and the slot of the state it came from
+        let start = (0, 0);
+        let states = dimensions.size() * directions;
+        let mut best = vec![UNREACHED; states];
+        let mut steps = vec![START; states];
+        let mut queue = BinaryHeap::from([Reverse((heuristic(&start), 0, start, None))]);
+        best[state(&start, None)?] = 0;
+
+        while let Some(Reverse((_, cost, current_pos, direction))) = queue.pop() {
+            let current = state(&current_pos, direction)?;
+            // a cheaper way was found after this one was queued
+            if cost > best[current] {
+                continue;
+            }
+            self.nodes.lower(current_pos, cost);
+
+            if current_pos == self.endpoint {
+                let offsets = self.edges.movement.offsets();
+                let mut path = vec![current_pos];
+                let mut current = current;
+                while steps[current] != START {
+                    let (arrival, from_slot) = (steps[current] & 0xF, steps[current] >> 4);
+                    let (x, y) = path.last().copied()?;
+                    let (dx, dy) = offsets[arrival as usize];
+                    let position = (x.checked_add_signed(-dx)?, y.checked_add_signed(-dy)?);
+                    current = dimensions.index(&position)? * directions + from_slot as usize;
+                    path.push(position);
                 }
-                if let Some(next_pos) = next_pos {
-                    current_pos = *next_pos;
-                } else {
-                    break;
+                path.reverse();
+                return Some(Route { cost, path });
+            }
+
+            let moves = self.edges.moves(&current_pos).into_iter().flatten();
+            for (next_direction, connection, step_cost) in moves {
+                let extra_cost = self.edges.movement.extra_cost(
+                    direction,
+                    next_direction,
+                    &current_pos,
+                    &connection,
+                );
+                let new_cost = cost + step_cost as usize + extra_cost;
+                let next = state(&connection, Some(next_direction))?;
+                if new_cost < best[next] {
+                    best[next] = new_cost;
+                    steps[next] = ((current % directions) << 4 | next_direction) as u8;
+                    queue.push(Reverse((
+                        new_cost + heuristic(&connection),
+                        new_cost,
+                        connection,
+                        Some(next_direction),
+                    )));
                 }
-            } else {
-                break;
             }
         }
+
+        None
     }
 
     pub fn draw(&self) {
@@ -264,12 +395,8 @@ impl Graph {
         }
     }
 
-    pub fn endpoint_score(&self) -> Option<&usize> {
-        if let Some(Some(score)) = self.nodes.get(&self.endpoint) {
-            Some(score)
-        } else {
-            None
-        }
+    pub fn endpoint_score(&self) -> Option<usize> {
+        self.nodes.get(&self.endpoint).flatten()
     }
 }
 
@@ -301,23 +428,6 @@ impl Graph {
 //     }
 // }
 
-#[derive(Debug)]
-pub struct Finder<'a> {
-    map: &'a Map,
-    route: Route,
-    scores: BTreeMap<(usize, usize), usize>,
-}
-
-impl<'a> Finder<'a> {
-    pub fn new(map: &'a Map) -> Finder<'a> {
-        Finder {
-            map,
-            route: Route::new(),
-            scores: BTreeMap::new(),
-        }
-    }
-}
-
 fn fetch_file_path() -> &'static str {
     if Path::new("src/input.txt").exists() {
         "src/input.txt"
@@ -340,27 +450,20 @@ fn part_one(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
 
     let map = Map::from_bufreader(buffer)?;
     let mut graph = Graph::from_map(&map);
-    graph.start_bruteforce();
+    let route = graph.start_dijkstra().ok_or("endpoint not reachable")?;
 
-    graph
-        .endpoint_score()
-        .copied()
-        .ok_or("endpoint score not found".into())
+    Ok(route.cost())
 }
 
 fn part_two(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
     let file = File::open(input_path)?;
     let buffer = BufReader::new(file);
-    let mut map = Map::from_bufreader(buffer)?;
-    map.foldout(5);
+    let map = Map::from_bufreader(buffer)?;
 
-    let mut graph = Graph::from_map(&map);
-    graph.start_bruteforce();
+    let mut graph = Graph::from_tiled(TiledMap::new(&map, 5), Movement::default());
+    let route = graph.start_dijkstra().ok_or("endpoint not reachable")?;
 
-    graph
-        .endpoint_score()
-        .copied()
-        .ok_or("endpoint score not found".into())
+    Ok(route.cost())
 }
 
 #[test]
@@ -368,8 +471,6 @@ fn day15_part_one() {
     assert_eq!(508, part_one(fetch_file_path()).unwrap())
 }
 
-// only possible to get if you run in release mode
-#[cfg(not(debug_assertions))]
 #[test]
 fn day15_part_two() {
     assert_eq!(2872, part_two(fetch_file_path()).unwrap())
@@ -394,7 +495,8 @@ mod map_test {
         ]);
         let map = Map::from_bufreader(BufReader::new(Cursor::new(text))).unwrap();
 
-        assert_eq!(map, expected)
+        assert_eq!(map, expected);
+        assert!(Map::from_bufreader(BufReader::new(Cursor::new("120\n456"))).is_err());
     }
 
     #[test]
@@ -429,7 +531,8 @@ mod map_test {
         ]);
 
         map.foldout(3);
-        assert_eq!(map, expected)
+        assert_eq!(map, expected);
+        assert!(Map::from_bufreader(BufReader::new(Cursor::new("120\n456"))).is_err());
     }
 
     #[test]
@@ -443,13 +546,15 @@ mod map_test {
         ]);
 
         map.foldout(2);
-        assert_eq!(map, expected)
+        assert_eq!(map, expected);
+        assert!(Map::from_bufreader(BufReader::new(Cursor::new("120\n456"))).is_err());
     }
 }
 
 #[cfg(test)]
 mod graph_test {
-    use super::{Graph, Map};
+    use super::{Graph, Map, Route};
+    use std::io::{BufReader, Cursor};
 
     #[test]
     fn from_map() {
@@ -457,21 +562,20 @@ mod graph_test {
 
         let graph = Graph::from_map(&map);
 
-        assert_eq!(&None, graph.nodes.get(&(0, 0)).unwrap());
-        assert_eq!(&None, graph.nodes.get(&(2, 1)).unwrap());
+        assert_eq!(Some(None), graph.nodes.get(&(0, 0)));
+        assert_eq!(Some(None), graph.nodes.get(&(2, 1)));
         assert_eq!(None, graph.nodes.get(&(5, 5)));
-        assert_eq!(&4, graph.edges.get(&(0, 0), &(0, 1)).unwrap());
-        assert_eq!(&8, graph.edges.get(&(2, 2), &(1, 2)).unwrap());
+        assert_eq!(Some(4), graph.edges.get(&(0, 0), &(0, 1)));
+        assert_eq!(Some(8), graph.edges.get(&(2, 2), &(1, 2)));
         assert_eq!(None, graph.edges.get(&(1, 1), &(1, 1)));
+        assert!(graph.edges.connections(&(3, 0)).is_none());
 
         assert_eq!(
             vec![((0, 1), 4), ((1, 0), 2)],
             graph
                 .edges
                 .connections(&(0, 0))
-                .cloned()
                 .unwrap()
-                .into_iter()
                 .collect::<Vec<_>>()
         );
 
@@ -480,10 +584,96 @@ mod graph_test {
             graph
                 .edges
                 .connections(&(1, 1))
-                .cloned()
                 .unwrap()
-                .into_iter()
                 .collect::<Vec<_>>()
         );
     }
+
+    const EXAMPLE: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+    fn example_map() -> Map {
+        Map::from_bufreader(BufReader::new(Cursor::new(EXAMPLE))).unwrap()
+    }
+
+    fn assert_valid_route(map: &Map, route: &Route) {
+        let path = route.path();
+        assert_eq!(Some(&(0, 0)), path.first());
+        assert_eq!(Some(&map.endpoint()), path.last());
+        for step in path.windows(2) {
+            let ((a, b), (c, d)) = (step[0], step[1]);
+            assert_eq!(1, a.abs_diff(c) + b.abs_diff(d));
+        }
+        let cost: usize = path[1..]
+            .iter()
+            .map(|position| map.risk(position).unwrap() as usize)
+            .sum();
+        assert_eq!(route.cost(), cost);
+    }
+
+    #[test]
+    fn dijkstra_example() {
+        let map = example_map();
+        let mut graph = Graph::from_map(&map);
+        let route = graph.start_dijkstra().unwrap();
+
+        assert_eq!(40, route.cost());
+        assert_valid_route(&map, &route);
+        assert_eq!(Some(40), graph.endpoint_score());
+        assert_eq!(Some(Some(0)), graph.nodes.get(&(0, 0)));
+    }
+
+    #[test]
+    fn a_star_example() {
+        let map = example_map();
+        let route = Graph::from_map(&map).start_a_star().unwrap();
+
+        assert_eq!(40, route.cost());
+        assert_valid_route(&map, &route);
+    }
+
+    #[test]
+    fn folded_example() {
+        let mut map = example_map();
+        map.foldout(5);
+
+        let dijkstra = Graph::from_map(&map).start_dijkstra().unwrap();
+        let a_star = Graph::from_map(&map).start_a_star().unwrap();
+
+        assert_eq!(315, dijkstra.cost());
+        assert_eq!(315, a_star.cost());
+        assert_valid_route(&map, &dijkstra);
+        assert_valid_route(&map, &a_star);
+    }
+
+    #[test]
+    fn winding_route() {
+        // the cheapest route has to go left all the way back
+        let map = Map::new(vec![
+            vec![1, 1, 1, 1, 1],
+            vec![9, 9, 9, 9, 1],
+            vec![9, 9, 9, 9, 1],
+            vec![1, 1, 1, 1, 1],
+            vec![1, 9, 9, 9, 9],
+            vec![1, 9, 9, 9, 9],
+            vec![1, 1, 1, 1, 1],
+        ]);
+        let route = Graph::from_map(&map).start_a_star().unwrap();
+
+        assert_eq!(
+            Graph::from_map(&map).start_dijkstra().unwrap().cost(),
+            route.cost()
+        );
+        assert_eq!(18, route.cost());
+        assert!(route.path().contains(&(0, 3)));
+        assert_valid_route(&map, &route);
+    }
 }
@@ -0,0 +1,261 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::Pair;
+
+/// offsets sorted the same way a `BTreeMap<Pair, _>` sorts the neighbours
+const FOUR: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const EIGHT: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Connectivity {
+    #[default]
+    Four,
+    Eight,
+}
+
+/// Extra cost of a move from the direction it arrived with, its own direction, both as
+/// indices in `Movement::offsets`, and the positions it goes from and to.
+pub type MoveCost = Rc<dyn Fn(Option<usize>, usize, &Pair, &Pair) -> usize>;
+
+/// Which moves are allowed on the grid and what they cost on top of the risk of the cell entered.
+#[derive(Default, Clone)]
+pub struct Movement {
+    pub connectivity: Connectivity,
+    /// cells that can never be entered
+    pub forbidden: BTreeSet<Pair>,
+    /// `(from, to)` edges that can not be walked in the direction `to` to `from`
+    pub one_way: BTreeSet<(Pair, Pair)>,
+    /// added up for every move
+    pub move_costs: Vec<MoveCost>,
+}
+
+impl std::fmt::Debug for Movement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Movement")
+            .field("connectivity", &self.connectivity)
+            .field("forbidden", &self.forbidden)
+            .field("one_way", &self.one_way)
+            .field("move_costs", &self.move_costs.len())
+            .finish()
+    }
+}
+
+impl Movement {
+    pub fn eight() -> Movement {
+        Movement {
+            connectivity: Connectivity::Eight,
+            ..Movement::default()
+        }
+    }
+
+    pub fn forbid(mut self, position: Pair) -> Movement {
+        self.forbidden.insert(position);
+        self
+    }
+
+    pub fn one_way(mut self, from: Pair, to: Pair) -> Movement {
+        self.one_way.insert((from, to));
+        self
+    }
+
+    /// adds `cost` to every move, the costs of all calls add up
+    pub fn with_move_cost<F>(mut self, cost: F) -> Movement
+    where
+        F: Fn(Option<usize>, usize, &Pair, &Pair) -> usize + 'static,
+    {
+        self.move_costs.push(Rc::new(cost));
+        self
+    }
+
+    /// extra cost for every move that changes direction
+    pub fn with_turn_penalty(self, penalty: usize) -> Movement {
+        self.with_move_cost(move |previous, direction, _, _| match previous {
+            Some(previous) if previous != direction => penalty,
+            _ => 0,
+        })
+    }
+
+    /// extra cost for every move that changes both coordinates
+    pub fn with_diagonal_cost(self, cost: usize) -> Movement {
+        self.with_move_cost(
+            move |_, _, (a, b), (c, d)| {
+                if a != c && b != d {
+                    cost
+                } else {
+                    0
+                }
+            },
+        )
+    }
+
+    /// moves cost more than their risk, possibly depending on the previous direction
+    pub fn has_move_costs(&self) -> bool {
+        !self.move_costs.is_empty()
+    }
+
+    pub fn offsets(&self) -> &'static [(isize, isize)] {
+        match self.connectivity {
+            Connectivity::Four => &FOUR,
+            Connectivity::Eight => &EIGHT,
+        }
+    }
+
+    /// allowed moves from a position as the index in `offsets` and the position it ends on
+    pub fn moves<'a>(&'a self, from: &Pair) -> impl Iterator<Item = (usize, Pair)> + 'a {
+        let (x, y) = *from;
+        let from = *from;
+        self.offsets()
+            .iter()
+            .enumerate()
+            .filter_map(move |(direction, (dx, dy))| {
+                let to = (x.checked_add_signed(*dx)?, y.checked_add_signed(*dy)?);
+                if self.forbidden.contains(&to) || self.one_way.contains(&(to, from)) {
+                    None
+                } else {
+                    Some((direction, to))
+                }
+            })
+    }
+
+    /// cost on top of the risk for moving in `direction` after arriving with `previous`
+    pub fn extra_cost(
+        &self,
+        previous: Option<usize>,
+        direction: usize,
+        from: &Pair,
+        to: &Pair,
+    ) -> usize {
+        self.move_costs
+            .iter()
+            .map(|cost| cost(previous, direction, from, to))
+            .sum()
+    }
+
+    /// Lower bound on the amount of moves between two positions. It is only an admissible a*
+    /// heuristic when every move costs at least 1, which holds as long as no risk is 0.
+    pub fn distance(&self, (a, b): &Pair, (c, d): &Pair) -> usize {
+        match self.connectivity {
+            Connectivity::Four => a.abs_diff(*c) + b.abs_diff(*d),
+            Connectivity::Eight => a.abs_diff(*c).max(b.abs_diff(*d)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod movement_tests {
+    use super::Movement;
+    use crate::{Graph, Map};
+
+    fn flat_map() -> Map {
+        Map::new(vec![vec![1; 4]; 4])
+    }
+
+    #[test]
+    fn moves() {
+        let movement = Movement::default().forbid((1, 0)).one_way((0, 1), (1, 1));
+
+        let moves: Vec<_> = movement.moves(&(1, 1)).map(|x| x.1).collect();
+        assert_eq!(vec![(1, 2), (2, 1)], moves);
+
+        let moves: Vec<_> = Movement::eight().moves(&(0, 0)).map(|x| x.1).collect();
+        assert_eq!(vec![(0, 1), (1, 0), (1, 1)], moves);
+    }
+
+    #[test]
+    fn eight_connectivity() {
+        let map = flat_map();
+        let route = Graph::from_map_with(&map, Movement::eight())
+            .start_a_star()
+            .unwrap();
+
+        assert_eq!(3, route.cost());
+        assert_eq!(&[(0, 0), (1, 1), (2, 2), (3, 3)], route.path());
+    }
+
+    #[test]
+    fn forbidden_cells() {
+        // a wall with a single gap at the bottom
+        let movement = Movement::default()
+            .forbid((1, 0))
+            .forbid((1, 1))
+            .forbid((1, 2));
+        let map = flat_map();
+        let route = Graph::from_map_with(&map, movement)
+            .start_dijkstra()
+            .unwrap();
+
+        assert_eq!(6, route.cost());
+        assert!(route.path().contains(&(1, 3)));
+    }
+
+    #[test]
+    fn one_way_edges() {
+        let map = Map::new(vec![vec![1, 1], vec![1, 1]]);
+        let movement = Movement::default()
+            .one_way((1, 1), (0, 1))
+            .one_way((1, 1), (1, 0));
+
+        assert!(Graph::from_map_with(&map, movement)
+            .start_dijkstra()
+            .is_none());
+    }
+
+    #[test]
+    fn turn_penalty() {
+        let map = flat_map();
+        let movement = Movement::default().with_turn_penalty(10);
+        let route = Graph::from_map_with(&map, movement).start_a_star().unwrap();
+
+        // a single turn
+        assert_eq!(16, route.cost());
+        assert!(route.path().contains(&(3, 0)) || route.path().contains(&(0, 3)));
+    }
+
+    #[test]
+    fn diagonal_cost() {
+        let map = flat_map();
+        let movement = Movement::eight().with_diagonal_cost(2);
+        let route = Graph::from_map_with(&map, movement).start_a_star().unwrap();
+
+        // three diagonal moves would cost 9
+        assert_eq!(6, route.cost());
+        assert!(route
+            .path()
+            .windows(2)
+            .all(|x| x[0].0 == x[1].0 || x[0].1 == x[1].1));
+
+        let movement = Movement::eight().with_diagonal_cost(1);
+        assert_eq!(
+            6,
+            Graph::from_map_with(&map, movement)
+                .start_a_star()
+                .unwrap()
+                .cost()
+        );
+    }
+
+    #[test]
+    fn move_cost_hook() {
+        // entering the first row is expensive, so the route goes down first
+        let map = flat_map();
+        let movement = Movement::default()
+            .with_move_cost(|_, _, _, to| if to.1 == 0 { 10 } else { 0 })
+            .with_turn_penalty(1);
+        let route = Graph::from_map_with(&map, movement)
+            .start_dijkstra()
+            .unwrap();
+
+        assert_eq!(7, route.cost());
+        assert_eq!(Some(&(0, 3)), route.path().get(3));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{Map, Route};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::{Map, Pair, Route};
 
 #[derive(Debug)]
 pub struct NaiveFinder<'a> {
@@ -78,3 +81,149 @@ impl<'a> NaiveFinder<'a> {
         Route::new()
     }
 }
+
+/// Depth first search that prunes on the best known score per cell and on the best
+/// complete route found so far. Only feasible for small maps.
+#[derive(Debug)]
+pub struct Finder<'a> {
+    map: &'a Map,
+    route: Route,
+    scores: BTreeMap<Pair, usize>,
+}
+
+impl<'a> Finder<'a> {
+    pub fn new(map: &'a Map) -> Finder<'a> {
+        Finder {
+            map,
+            route: Route::new(),
+            scores: BTreeMap::new(),
+        }
+    }
+
+    /// cost of going along the top row and then down the last column
+    fn upper_bound(&self) -> Option<usize> {
+        let (end_x, end_y) = self.map.endpoint();
+        let top = (1..=end_x).map(|x| self.map.risk(&(x, 0)));
+        let right = (1..=end_y).map(|y| self.map.risk(&(end_x, y)));
+        top.chain(right).map(|risk| risk.map(|x| x as usize)).sum()
+    }
+
+    pub fn start(&mut self) -> Option<Route> {
+        let endpoint = self.map.endpoint();
+        let mut bound = self.upper_bound()?;
+        let mut best: Option<Route> = None;
+        let mut stack = vec![((0, 0), 0, 0)];
+        self.route = Route::new();
+        self.scores = BTreeMap::from([((0, 0), 0)]);
+
+        while let Some((position, cost, depth)) = stack.pop() {
+            // a cheaper way to this cell was found after this one was pushed
+            if self.scores.get(&position).is_some_and(|x| *x < cost) {
+                continue;
+            }
+            self.route.path.truncate(depth);
+            self.route.path.push(position);
+            self.route.cost = cost;
+
+            if position == endpoint {
+                if best.as_ref().is_none_or(|x| cost < x.cost) {
+                    bound = cost;
+                    best = Some(self.route.clone());
+                }
+                continue;
+            }
+
+            let mut next_steps = Vec::new();
+            for step in self.route.next_steps() {
+                let risk = match self.map.risk(&step) {
+                    Some(risk) => risk as usize,
+                    None => continue,
+                };
+                let new_cost = cost + risk;
+                let remaining = step.0.abs_diff(endpoint.0) + step.1.abs_diff(endpoint.1);
+                if new_cost + remaining > bound
+                    || self.scores.get(&step).is_some_and(|x| *x <= new_cost)
+                {
+                    continue;
+                }
+                self.scores.insert(step, new_cost);
+                next_steps.push((risk, step, new_cost));
+            }
+
+            // lowest risk is explored first
+            next_steps.sort_by_key(|(risk, _, _)| Reverse(*risk));
+            for (_, step, new_cost) in next_steps {
+                stack.push((step, new_cost, depth + 1));
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod finder_tests {
+    use super::Finder;
+    use crate::{Graph, Map};
+
+    /// deterministic risk levels from a linear congruential generator
+    fn pseudo_random_map(width: usize, height: usize, seed: u64) -> Map {
+        let mut state = seed;
+        let data = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        ((state >> 33) % 9) as u8 + 1
+                    })
+                    .collect()
+            })
+            .collect();
+        Map::new(data)
+    }
+
+    #[test]
+    fn example() {
+        let map = Map::new(
+            [
+                "1163751742",
+                "1381373672",
+                "2136511328",
+                "3694931569",
+                "7463417111",
+                "1319128137",
+                "1359912421",
+                "3125421639",
+                "1293138521",
+                "2311944581",
+            ]
+            .iter()
+            .map(|line| line.bytes().map(|x| x - b'0').collect())
+            .collect(),
+        );
+
+        let route = Finder::new(&map).start().unwrap();
+        assert_eq!(40, route.cost());
+        assert_eq!(Some(&(0, 0)), route.path().first());
+        assert_eq!(Some(&(9, 9)), route.path().last());
+    }
+
+    #[test]
+    fn cross_check_dijkstra() {
+        for (seed, (width, height)) in [(3, 3), (8, 8), (12, 7), (5, 15), (15, 15)]
+            .into_iter()
+            .enumerate()
+        {
+            let map = pseudo_random_map(width, height, seed as u64);
+            let route = Finder::new(&map).start().unwrap();
+            let expected = Graph::from_map(&map).start_dijkstra().unwrap();
+
+            assert_eq!(expected.cost(), route.cost());
+            let cost: usize = route.path()[1..]
+                .iter()
+                .map(|position| map.risk(position).unwrap() as usize)
+                .sum();
+            assert_eq!(route.cost(), cost);
+        }
+    }
+}
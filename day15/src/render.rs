@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use crate::{Map, Route};
+
+const PATH_COLOR: (u8, u8, u8) = (255, 255, 255);
+
+/// green for the lowest risk, red for the highest
+pub fn heat(risk: u8) -> (u8, u8, u8) {
+    let step = (risk.clamp(1, 9) - 1) as u16;
+    let red = step * 255 / 8;
+    (red as u8, (255 - red) as u8, 0)
+}
+
+impl Map {
+    fn route_cells(route: &Route) -> BTreeSet<(usize, usize)> {
+        route.path().iter().copied().collect()
+    }
+
+    /// risk levels as a colored heatmap with the route in bold on a blue background
+    pub fn render(&self, route: &Route) -> String {
+        let cells = Self::route_cells(route);
+        let mut output = String::new();
+        for (y, line) in self.iter().enumerate() {
+            for (x, risk) in line.iter().enumerate() {
+                if cells.contains(&(x, y)) {
+                    output.push_str(&format!("\x1b[1;97;44m{}", risk));
+                } else {
+                    let (r, g, b) = heat(*risk);
+                    output.push_str(&format!("\x1b[38;2;{};{};{}m{}", r, g, b, risk));
+                }
+            }
+            output.push_str("\x1b[0m\n");
+        }
+        output
+    }
+
+    /// binary PPM with a pixel per cell, the route is drawn in white
+    pub fn write_ppm<W: Write>(&self, route: &Route, mut writer: W) -> std::io::Result<()> {
+        let cells = Self::route_cells(route);
+        write!(writer, "P6\n{} {}\n255\n", self.max_x(), self.max_y())?;
+        for (y, line) in self.iter().enumerate() {
+            let mut row = Vec::with_capacity(line.len() * 3);
+            for (x, risk) in line.iter().enumerate() {
+                let (r, g, b) = if cells.contains(&(x, y)) {
+                    PATH_COLOR
+                } else {
+                    heat(*risk)
+                };
+                row.extend_from_slice(&[r, g, b]);
+            }
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// SVG with a square per cell and the route as a polyline through the cell centres
+    pub fn write_svg<W: Write>(&self, route: &Route, mut writer: W) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+            self.max_x(),
+            self.max_y()
+        )?;
+        for (y, line) in self.iter().enumerate() {
+            for (x, risk) in line.iter().enumerate() {
+                let (r, g, b) = heat(*risk);
+                writeln!(
+                    writer,
+                    r#"<rect x="{}" y="{}" width="1" height="1" fill="rgb({},{},{})"/>"#,
+                    x, y, r, g, b
+                )?;
+            }
+        }
+
+        let points: Vec<_> = route
+            .path()
+            .iter()
+            .map(|(x, y)| format!("{}.5,{}.5", x, y))
+            .collect();
+        let (r, g, b) = PATH_COLOR;
+        writeln!(
+            writer,
+            r#"<polyline points="{}" fill="none" stroke="rgb({},{},{})" stroke-width="0.3"/>"#,
+            points.join(" "),
+            r,
+            g,
+            b
+        )?;
+        writeln!(writer, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::heat;
+    use crate::{Graph, Map};
+
+    fn map_and_route() -> (Map, crate::Route) {
+        let map = Map::new(vec![vec![1, 9], vec![5, 1]]);
+        let route = Graph::from_map(&map).start_dijkstra().unwrap();
+        (map, route)
+    }
+
+    #[test]
+    fn heat_range() {
+        assert_eq!((0, 255, 0), heat(1));
+        assert_eq!((255, 0, 0), heat(9));
+    }
+
+    #[test]
+    fn render_ansi() {
+        let (map, route) = map_and_route();
+
+        assert_eq!(
+            "\x1b[1;97;44m1\x1b[38;2;255;0;0m9\x1b[0m\n\x1b[1;97;44m5\x1b[1;97;44m1\x1b[0m\n",
+            map.render(&route)
+        );
+    }
+
+    #[test]
+    fn ppm() {
+        let (map, route) = map_and_route();
+        let mut output = Vec::new();
+        map.write_ppm(&route, &mut output).unwrap();
+
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(header, &output[..header.len()]);
+        assert_eq!(header.len() + 2 * 2 * 3, output.len());
+        assert_eq!(&[255, 0, 0], &output[header.len() + 3..header.len() + 6]);
+    }
+
+    #[test]
+    fn svg() {
+        let (map, route) = map_and_route();
+        let mut output = Vec::new();
+        map.write_svg(&route, &mut output).unwrap();
+        let svg = String::from_utf8(output).unwrap();
+
+        assert_eq!(4, svg.matches("<rect").count());
+        assert!(svg.contains(r#"<polyline points="0.5,0.5 0.5,1.5 1.5,1.5""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{Map, Pair};
+
+/// The map repeated `amount` times in both directions, with the risk increasing by one
+/// for every tile to the right or down. Only the first tile is stored, row by row.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TiledMap {
+    width: usize,
+    height: usize,
+    risks: Vec<u8>,
+    amount: usize,
+}
+
+impl TiledMap {
+    pub fn new(map: &Map, amount: usize) -> TiledMap {
+        TiledMap {
+            width: map.first().map_or(0, |x| x.len()),
+            height: map.len(),
+            risks: map.iter().flatten().copied().collect(),
+            amount,
+        }
+    }
+
+    /// same indexing as `Map::get`, row first
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        self.risk(&(y, x))
+    }
+
+    /// risk at a `(column, row)` position
+    pub fn risk(&self, (x, y): &Pair) -> Option<u8> {
+        // the first tile is looked up directly
+        if *x < self.width && *y < self.height {
+            return Some(self.risks[y * self.width + x]);
+        }
+        if *x >= self.max_x() || *y >= self.max_y() {
+            return None;
+        }
+        let cell = self.risks[(y % self.height) * self.width + x % self.width] as usize;
+        let tile_x = x / self.width;
+        let tile_y = y / self.height;
+        Some(((cell - 1 + tile_x + tile_y) % 9 + 1) as u8)
+    }
+
+    pub fn endpoint(&self) -> (usize, usize) {
+        (self.max_x() - 1, self.max_y() - 1)
+    }
+
+    pub fn max_x(&self) -> usize {
+        self.width * self.amount
+    }
+
+    pub fn max_y(&self) -> usize {
+        self.height * self.amount
+    }
+}
+
+#[cfg(test)]
+mod tiled_map_test {
+    use super::TiledMap;
+    use crate::movement::Movement;
+    use crate::{Graph, Map};
+
+    #[test]
+    fn same_as_foldout() {
+        let map = Map::new(vec![vec![9, 7, 1], vec![8, 9, 5], vec![2, 3, 4]]);
+        let tiled = TiledMap::new(&map, 4);
+        let mut folded = map;
+        folded.foldout(4);
+
+        assert_eq!(folded.max_x(), tiled.max_x());
+        assert_eq!(folded.max_y(), tiled.max_y());
+        assert_eq!(folded.endpoint(), tiled.endpoint());
+        for x in 0..=folded.max_y() {
+            for y in 0..=folded.max_x() {
+                assert_eq!(folded.get(x, y).copied(), tiled.get(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn rectangular() {
+        let tiled = TiledMap::new(&Map::new(vec![vec![1, 2, 3]]), 2);
+
+        assert_eq!((5, 1), tiled.endpoint());
+        assert_eq!(Some(4), tiled.risk(&(5, 0)));
+        assert_eq!(Some(3), tiled.risk(&(1, 1)));
+        assert_eq!(None, tiled.risk(&(6, 0)));
+    }
+
+    #[test]
+    fn large_foldout() {
+        let map = Map::new(vec![vec![1, 1, 6], vec![1, 3, 8], vec![2, 1, 3]]);
+        let tiled = TiledMap::new(&map, 100);
+        let route = Graph::from_tiled(tiled, Movement::default())
+            .start_a_star()
+            .unwrap();
+
+        let mut folded = map;
+        folded.foldout(100);
+        let expected = Graph::from_map(&folded).start_dijkstra().unwrap();
+
+        assert_eq!(Some(&(299, 299)), route.path().last());
+        assert_eq!(expected.cost(), route.cost());
+    }
+}
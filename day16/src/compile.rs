@@ -0,0 +1,293 @@
+use crate::{OperatorType, Packet, PacketType};
+
+/// the value of a compiled packet, or why the operators could not be applied
+pub type Value = Result<u128, Box<dyn std::error::Error>>;
+pub type Closure = Box<dyn Fn(&[u128]) -> Value>;
+
+/// Deepest packet tree the closure compilers accept. Calling and dropping a closure tree
+/// recurses once per level, deeper trees would overflow the stack, `Program` has no limit.
+pub const MAX_CLOSURE_DEPTH: usize = 1_000;
+
+/// Literal values of the packet, in the order they are used as template parameters.
+pub fn literals(packet: &Packet) -> Vec<u128> {
+    packet
+        .pre_order()
+        .filter_map(|x| match x.packet_type {
+            PacketType::Literal(value) => Some(value as u128),
+            PacketType::Operator(_) => None,
+        })
+        .collect()
+}
+
+fn build_closure(packet: &Packet, template: bool) -> Result<Closure, Box<dyn std::error::Error>> {
+    let depth = packet.depth();
+    if depth > MAX_CLOSURE_DEPTH {
+        return Err(format!(
+            "packet is nested {} levels deep, closures support up to {}",
+            depth, MAX_CLOSURE_DEPTH
+        )
+        .into());
+    }
+
+    let mut closures: Vec<Closure> = Vec::new();
+    let mut parameter = 0;
+    for packet in packet.post_order() {
+        let closure: Closure = match &packet.packet_type {
+            PacketType::Literal(_) if template => {
+                let index = parameter;
+                parameter += 1;
+                Box::new(move |parameters| {
+                    Ok(*parameters.get(index).ok_or("missing template parameter")?)
+                })
+            }
+            PacketType::Literal(value) => {
+                let value = *value as u128;
+                Box::new(move |_| Ok(value))
+            }
+            PacketType::Operator(operator) => {
+                let at = closures.len() - operator.sub_packets.len();
+                let sub_closures = closures.split_off(at);
+                let id = operator.id;
+                Box::new(move |parameters| {
+                    let values = sub_closures
+                        .iter()
+                        .map(|x| x(parameters))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    id.apply_values(&values)
+                })
+            }
+        };
+        closures.push(closure);
+    }
+    Ok(closures.pop().expect("walk always yields the root packet"))
+}
+
+/// Compiles the packet into a closure tree with the literals baked in, fails for trees
+/// deeper than `MAX_CLOSURE_DEPTH`.
+pub fn compile_closure(
+    packet: &Packet,
+) -> Result<Box<dyn Fn() -> Value>, Box<dyn std::error::Error>> {
+    let closure = build_closure(packet, false)?;
+    Ok(Box::new(move || closure(&[])))
+}
+
+/// Compiles the packet into a closure tree that reads every literal from its parameters, fails
+/// for trees deeper than `MAX_CLOSURE_DEPTH`.
+pub fn compile_template_closure(packet: &Packet) -> Result<Closure, Box<dyn std::error::Error>> {
+    build_closure(packet, true)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Instruction {
+    Push(u128),
+    Parameter(usize),
+    Apply(OperatorType, usize),
+}
+
+/// Bytecode for a small stack machine, operators pop their sub packet values.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    parameters: usize,
+    max_stack: usize,
+}
+
+impl Program {
+    fn build(packet: &Packet, template: bool) -> Program {
+        let mut instructions = Vec::new();
+        let mut parameters = 0;
+        let mut stack = 0;
+        let mut max_stack = 0;
+        for packet in packet.post_order() {
+            let instruction = match &packet.packet_type {
+                PacketType::Literal(_) if template => {
+                    parameters += 1;
+                    Instruction::Parameter(parameters - 1)
+                }
+                PacketType::Literal(value) => Instruction::Push(*value as u128),
+                PacketType::Operator(operator) => {
+                    stack -= operator.sub_packets.len();
+                    Instruction::Apply(operator.id, operator.sub_packets.len())
+                }
+            };
+            stack += 1;
+            max_stack = max_stack.max(stack);
+            instructions.push(instruction);
+        }
+
+        Program {
+            instructions,
+            parameters,
+            max_stack,
+        }
+    }
+
+    pub fn compile(packet: &Packet) -> Program {
+        Program::build(packet, false)
+    }
+
+    pub fn compile_template(packet: &Packet) -> Program {
+        Program::build(packet, true)
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// amount of parameters `run` expects
+    pub fn parameters(&self) -> usize {
+        self.parameters
+    }
+
+    pub fn run(&self, parameters: &[u128]) -> Value {
+        if self.parameters != parameters.len() {
+            return Err(format!(
+                "expected {} parameters, got {}",
+                self.parameters,
+                parameters.len()
+            )
+            .into());
+        }
+
+        let mut stack = Vec::with_capacity(self.max_stack);
+        for instruction in &self.instructions {
+            let value = match *instruction {
+                Instruction::Push(value) => value,
+                Instruction::Parameter(index) => parameters[index],
+                Instruction::Apply(id, arity) => {
+                    let at = stack.len() - arity;
+                    let value = id.apply_values(&stack[at..])?;
+                    stack.truncate(at);
+                    value
+                }
+            };
+            stack.push(value);
+        }
+        Ok(stack.pop().expect("program always leaves a value"))
+    }
+}
+
+#[cfg(test)]
+mod compile_tests {
+    use super::{
+        compile_closure, compile_template_closure, literals, Instruction, Program, Value,
+        MAX_CLOSURE_DEPTH,
+    };
+    use crate::{fetch_file_path, nested_bits, OperatorType, Packet};
+    use std::fs::read_to_string;
+    use std::time::Instant;
+
+    const EXAMPLES: [(&str, u128); 8] = [
+        ("C200B40A82", 3),
+        ("04005AC33890", 54),
+        ("880086C3E88112", 7),
+        ("CE00C43D881120", 9),
+        ("D8005AC2A8F0", 1),
+        ("F600BC2D8F", 0),
+        ("9C005AC2F8F0", 0),
+        ("9C0141080250320F1802104A08", 1),
+    ];
+
+    #[test]
+    fn compiled_examples() {
+        for (data, expected) in EXAMPLES {
+            let packet = Packet::from_hex(data).unwrap();
+            let parameters = literals(&packet);
+
+            assert_eq!(expected, compile_closure(&packet).unwrap()().unwrap());
+            assert_eq!(
+                expected,
+                compile_template_closure(&packet).unwrap()(&parameters).unwrap()
+            );
+            assert_eq!(expected, Program::compile(&packet).run(&[]).unwrap());
+            assert_eq!(
+                expected,
+                Program::compile_template(&packet).run(&parameters).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn bytecode() {
+        let packet = Packet::from_hex("C200B40A82").unwrap();
+
+        assert_eq!(
+            &[
+                Instruction::Push(1),
+                Instruction::Push(2),
+                Instruction::Apply(OperatorType::Sum, 2)
+            ],
+            Program::compile(&packet).instructions()
+        );
+    }
+
+    #[test]
+    fn template_substitution() {
+        // 1 + 2
+        let packet = Packet::from_hex("C200B40A82").unwrap();
+        let program = Program::compile_template(&packet);
+        let closure = compile_template_closure(&packet).unwrap();
+
+        assert_eq!(2, program.parameters());
+        assert_eq!(
+            u64::MAX as u128 * 2,
+            program.run(&[u64::MAX as u128; 2]).unwrap()
+        );
+        assert_eq!(42, closure(&[40, 2]).unwrap());
+
+        // the parameters can overflow the sum or be missing
+        assert!(program.run(&[u128::MAX, 1]).is_err());
+        assert!(closure(&[u128::MAX, 1]).is_err());
+        assert!(program.run(&[1]).is_err());
+        assert!(closure(&[1]).is_err());
+    }
+
+    #[test]
+    fn compiled_input() {
+        let data = read_to_string(fetch_file_path()).unwrap();
+        let packet = Packet::from_hex(data.trim()).unwrap();
+
+        assert_eq!(1725277876501, compile_closure(&packet).unwrap()().unwrap());
+        assert_eq!(1725277876501, Program::compile(&packet).run(&[]).unwrap());
+    }
+
+    #[test]
+    fn nesting_limit() {
+        // the literal adds a level to the operators
+        let packet = Packet::from_bytes(&nested_bits(MAX_CLOSURE_DEPTH - 1)).unwrap();
+        assert_eq!(7, compile_closure(&packet).unwrap()().unwrap());
+        assert_eq!(7, compile_template_closure(&packet).unwrap()(&[7]).unwrap());
+
+        let packet = Packet::from_bytes(&nested_bits(MAX_CLOSURE_DEPTH)).unwrap();
+        assert!(compile_closure(&packet).is_err());
+
+        let packet = Packet::from_bytes(&nested_bits(100_000)).unwrap();
+        assert!(compile_closure(&packet).is_err());
+        assert!(compile_template_closure(&packet).is_err());
+        assert_eq!(7, Program::compile(&packet).run(&[]).unwrap());
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_tree_walking_vs_bytecode() {
+        let data = read_to_string(fetch_file_path()).unwrap();
+        let packet = Packet::from_hex(data.trim()).unwrap();
+        let program = Program::compile_template(&packet);
+        let closure = compile_template_closure(&packet).unwrap();
+        let parameters = literals(&packet);
+        let rounds = 10_000;
+
+        let evaluators: [(&str, &dyn Fn() -> Value); 3] = [
+            ("tree walking value()", &|| Ok(packet.value()? as u128)),
+            ("closure tree", &|| closure(&parameters)),
+            ("bytecode", &|| program.run(&parameters)),
+        ];
+        for (label, evaluate) in evaluators {
+            let start = Instant::now();
+            for _ in 0..rounds {
+                assert_eq!(1725277876501, evaluate().unwrap());
+            }
+            println!("{}: {:?}", label, start.elapsed() / rounds);
+        }
+    }
+}
@@ -1,6 +1,13 @@
-use std::fs::read_to_string;
+use std::fs::File;
 use std::path::Path;
 
+pub mod compile;
+pub mod stats;
+pub mod stream;
+pub mod validate;
+
+use validate::{Mode, ValidationError, Violation};
+
 fn fetch_file_path() -> &'static str {
     if Path::new("src/input.txt").exists() {
         "src/input.txt"
@@ -18,26 +25,165 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn part_one(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
-    let data = read_to_string(input_path)?;
-    let packet = Packet::from_hex(&data)?;
-    Ok(packet.count_versions())
+    let file = File::open(input_path)?;
+    stream::count_versions(file)
 }
 
 fn part_two(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
-    let data = read_to_string(input_path)?;
-    let packet = Packet::from_hex(&data)?;
-    Ok(packet.value())
+    let file = File::open(input_path)?;
+    stream::evaluate(file)
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Packet {
     version: u8,
     packet_type: PacketType,
+    bits: usize,
 }
 
 impl Packet {
-    pub fn value(&self) -> usize {
-        self.packet_type.value()
+    /// fails for packets a lenient parse lets through, like a comparison with a single sub
+    /// packet, and for values that overflow
+    pub fn value(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut values = Vec::new();
+        for packet in self.post_order() {
+            match &packet.packet_type {
+                PacketType::Literal(value) => values.push(*value as u128),
+                PacketType::Operator(operator) => {
+                    let at = values.len() - operator.sub_packets.len();
+                    let value = operator.id.apply_values(&values[at..])?;
+                    values.truncate(at);
+                    values.push(value);
+                }
+            }
+        }
+        to_usize(values.pop().expect("walk always yields the root packet"))
+    }
+
+    pub fn sub_packets(&self) -> &[Packet] {
+        match &self.packet_type {
+            PacketType::Literal(_) => &[],
+            PacketType::Operator(operator) => &operator.sub_packets,
+        }
+    }
+
+    /// Pre-order iterator over all packets together with the sub packet indices leading to them.
+    /// Every item owns its path, on deep trees `for_each_path` avoids the copies.
+    pub fn paths(&self) -> impl Iterator<Item = (Vec<usize>, &Packet)> {
+        let mut stack = vec![(Vec::new(), self)];
+        std::iter::from_fn(move || {
+            let (path, packet) = stack.pop()?;
+            for (index, child) in packet.sub_packets().iter().enumerate().rev() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                stack.push((child_path, child));
+            }
+            Some((path, packet))
+        })
+    }
+
+    /// Calls `visit` for all packets in pre-order with the sub packet indices leading to them,
+    /// the indices are borrowed from a single stack.
+    pub fn for_each_path<F: FnMut(&[usize], &Packet)>(&self, mut visit: F) {
+        let mut path = Vec::new();
+        // index of the next sub packet of every open packet
+        let mut next = Vec::new();
+        for event in self.walk() {
+            match event {
+                WalkEvent::Enter(packet) => {
+                    if let Some(index) = next.last_mut() {
+                        path.push(*index);
+                        *index += 1;
+                    }
+                    visit(&path, packet);
+                    next.push(0);
+                }
+                WalkEvent::Leave(_) => {
+                    next.pop();
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    /// Iterates over all packets, yielding each one when entered and when left.
+    pub fn walk(&self) -> Walk<'_> {
+        Walk {
+            start: Some(self),
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn pre_order(&self) -> impl Iterator<Item = &Packet> {
+        self.walk().filter_map(|event| match event {
+            WalkEvent::Enter(packet) => Some(packet),
+            WalkEvent::Leave(_) => None,
+        })
+    }
+
+    pub fn post_order(&self) -> impl Iterator<Item = &Packet> {
+        self.walk().filter_map(|event| match event {
+            WalkEvent::Enter(_) => None,
+            WalkEvent::Leave(packet) => Some(packet),
+        })
+    }
+
+    /// Feeds the packet tree to a visitor, in the same order the stream decoder would.
+    pub fn visit<V: stream::Visitor>(
+        &self,
+        visitor: &mut V,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for event in self.walk() {
+            match event {
+                WalkEvent::Enter(packet) => match &packet.packet_type {
+                    PacketType::Literal(value) => visitor.literal(packet.version, *value)?,
+                    PacketType::Operator(operator) => {
+                        visitor.enter_operator(packet.version, operator.id)?
+                    }
+                },
+                WalkEvent::Leave(Packet {
+                    packet_type: PacketType::Operator(operator),
+                    ..
+                }) => visitor.exit_operator(operator.id)?,
+                WalkEvent::Leave(_) => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WalkEvent<'a> {
+    Enter(&'a Packet),
+    Leave(&'a Packet),
+}
+
+pub struct Walk<'a> {
+    start: Option<&'a Packet>,
+    stack: Vec<(&'a Packet, usize)>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = WalkEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(packet) = self.start.take() {
+            self.stack.push((packet, 0));
+            return Some(WalkEvent::Enter(packet));
+        }
+
+        let (packet, index) = self.stack.last_mut()?;
+        match packet.sub_packets().get(*index) {
+            Some(child) => {
+                *index += 1;
+                self.stack.push((child, 0));
+                Some(WalkEvent::Enter(child))
+            }
+            None => {
+                let (packet, _) = self.stack.pop()?;
+                Some(WalkEvent::Leave(packet))
+            }
+        }
     }
 }
 
@@ -48,10 +194,10 @@ pub enum PacketType {
 }
 
 impl PacketType {
-    pub fn value(&self) -> usize {
+    pub fn value(&self) -> Result<usize, Box<dyn std::error::Error>> {
         use PacketType::*;
         match self {
-            Literal(value) => *value,
+            Literal(value) => Ok(*value),
             Operator(operator) => operator.value(),
         }
     }
@@ -68,12 +214,74 @@ impl Operator {
         self.sub_packets.iter().map(|x| x.count_versions()).sum()
     }
 
-    pub fn value(&self) -> usize {
+    pub fn value(&self) -> Result<usize, Box<dyn std::error::Error>> {
         self.id.apply(&self.sub_packets)
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl Drop for Operator {
+    fn drop(&mut self) {
+        // flatten the tree first so deeply nested packets don't overflow the stack
+        let mut packets = std::mem::take(&mut self.sub_packets);
+        while let Some(packet) = packets.pop() {
+            if let PacketType::Operator(mut operator) = packet.packet_type {
+                packets.append(&mut operator.sub_packets);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Length {
+    Bits(usize),
+    Packets(usize),
+}
+
+#[derive(Debug)]
+struct PendingOperator {
+    start: usize,
+    version: u8,
+    id: OperatorType,
+    remaining: Length,
+    sub_packets: Vec<Packet>,
+}
+
+impl PendingOperator {
+    /// sub packets may run past the declared amount of bits, that is checked in `overrun`
+    fn is_complete(&self, position: usize) -> bool {
+        match self.remaining {
+            Length::Bits(end) => position >= end,
+            Length::Packets(amount) => amount == 0,
+        }
+    }
+
+    /// a violation if the sub packets used more bits than the operator declared, `parents`
+    /// are the operators still open around it
+    fn overrun(&self, parents: &[PendingOperator], position: usize) -> Option<Violation> {
+        match self.remaining {
+            Length::Bits(end) if position > end => Some(Violation::LengthOverrun {
+                path: parents.iter().map(|x| x.sub_packets.len()).collect(),
+                id: self.id,
+                end,
+                position,
+            }),
+            _ => None,
+        }
+    }
+
+    fn into_packet(self, position: usize) -> Packet {
+        Packet {
+            version: self.version,
+            packet_type: PacketType::Operator(Operator {
+                id: self.id,
+                sub_packets: self.sub_packets,
+            }),
+            bits: position - self.start,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum OperatorType {
     Sum,
     Product,
@@ -84,10 +292,14 @@ pub enum OperatorType {
     Equal,
 }
 
-impl From<u8> for OperatorType {
-    fn from(input: u8) -> OperatorType {
+/// Every three bit type id is an operator except 4, which is a literal and is handled by
+/// the decoders before they get here.
+impl TryFrom<u8> for OperatorType {
+    type Error = String;
+
+    fn try_from(input: u8) -> Result<OperatorType, String> {
         use OperatorType::*;
-        match input {
+        Ok(match input {
             0 => Sum,
             1 => Product,
             2 => Minimum,
@@ -95,62 +307,66 @@ impl From<u8> for OperatorType {
             5 => GreaterThan,
             6 => LessThan,
             7 => Equal,
-            _ => unreachable!(),
-        }
+            type_id => return Err(format!("type id {} is not an operator", type_id)),
+        })
     }
 }
 
+fn to_usize(value: u128) -> Result<usize, Box<dyn std::error::Error>> {
+    Ok(usize::try_from(value).map_err(|_| format!("{} does not fit in usize", value))?)
+}
+
 impl OperatorType {
-    pub fn apply(&self, packets: &Vec<Packet>) -> usize {
+    pub fn apply(&self, packets: &[Packet]) -> Result<usize, Box<dyn std::error::Error>> {
+        let values = packets
+            .iter()
+            .map(|x| Ok(x.value()? as u128))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        to_usize(self.apply_values(&values)?)
+    }
+
+    /// Combines the values of the sub packets. Comparisons need exactly two of them and sums
+    /// and products fail instead of overflowing.
+    pub fn apply_values(&self, values: &[u128]) -> Result<u128, Box<dyn std::error::Error>> {
         use OperatorType::*;
-        match self {
-            Sum => packets.into_iter().map(|x| x.value()).sum(),
-            Product => packets.into_iter().map(|x| x.value()).product(),
-            Maximum => packets
-                .into_iter()
-                .map(|x| x.value())
-                .max()
-                .unwrap_or(usize::MIN),
-            Minimum => packets
-                .into_iter()
-                .map(|x| x.value())
-                .min()
-                .unwrap_or(usize::MAX),
+        let pair = || match values {
+            [a, b] => Ok((a, b)),
+            _ => Err(format!(
+                "{:?} expects two sub packets, got {}",
+                self,
+                values.len()
+            )),
+        };
+        Ok(match self {
+            Sum => values
+                .iter()
+                .try_fold(0u128, |acc, x| acc.checked_add(*x))
+                .ok_or("sum does not fit in u128")?,
+            Product => values
+                .iter()
+                .try_fold(1u128, |acc, x| acc.checked_mul(*x))
+                .ok_or("product does not fit in u128")?,
+            Maximum => values.iter().copied().max().unwrap_or(u128::MIN),
+            Minimum => values.iter().copied().min().unwrap_or(u128::MAX),
             GreaterThan => {
-                let a = packets
-                    .get(0)
-                    .expect("greater than expects two sub packets");
-                let b = packets
-                    .get(1)
-                    .expect("greater than expects two sub packets");
-                if a.value() > b.value() {
-                    1
-                } else {
-                    0
-                }
+                let (a, b) = pair()?;
+                (a > b) as u128
             }
             LessThan => {
-                let a = packets.get(0).expect("less than expects two sub packets");
-                let b = packets.get(1).expect("less than expects two sub packets");
-                if a.value() < b.value() {
-                    1
-                } else {
-                    0
-                }
+                let (a, b) = pair()?;
+                (a < b) as u128
             }
             Equal => {
-                let a = packets.get(0).expect("equal expects two sub packets");
-                let b = packets.get(1).expect("equal expects two sub packets");
-                if a.value() == b.value() {
-                    1
-                } else {
-                    0
-                }
+                let (a, b) = pair()?;
+                (a == b) as u128
             }
-        }
+        })
     }
 }
 
+/// what was split off the front of the bits, and the bits after it
+type Split<'a, T> = Result<(T, &'a [u8]), Box<dyn std::error::Error>>;
+
 fn parse_hex(ch: char) -> Option<&'static [u8]> {
     Some(match ch {
         '0' => &[0, 0, 0, 0],
@@ -175,6 +391,10 @@ fn parse_hex(ch: char) -> Option<&'static [u8]> {
 
 impl Packet {
     pub fn from_hex(input: &str) -> Result<Packet, Box<dyn std::error::Error>> {
+        Packet::from_hex_with(input, Mode::Lenient)
+    }
+
+    pub fn from_hex_with(input: &str, mode: Mode) -> Result<Packet, Box<dyn std::error::Error>> {
         let data = input
             .chars()
             .try_fold(Vec::new(), |mut acc, ch| {
@@ -183,28 +403,42 @@ impl Packet {
             })
             .ok_or("invalid hexdata")?;
 
-        Packet::from_bytes(&data)
+        Packet::from_bytes_with(&data, mode)
     }
 
     pub fn from_bytes(input: &[u8]) -> Result<Packet, Box<dyn std::error::Error>> {
-        let (packet, data, eaten_bits) = Self::split_packet(input);
+        Packet::from_bytes_with(input, Mode::Lenient)
+    }
+
+    pub fn from_bytes_with(input: &[u8], mode: Mode) -> Result<Packet, Box<dyn std::error::Error>> {
+        let ((packet, eaten_bits, overruns), data) = Self::split_packet(input)?;
 
         // trim zeroes
         let amount_of_padding_bits = (8 - (eaten_bits % 8)) % 8;
-        let (_padding, data) = data.split_at(amount_of_padding_bits);
+        let (padding, data) = data.split_at(amount_of_padding_bits.min(data.len()));
+
+        if !data.is_empty() {
+            return Err("data remaining".into());
+        }
 
-        if data.len() != 0 {
-            Err("data remaining".into())
-        } else {
-            Ok(packet)
+        if mode == Mode::Strict {
+            let mut violations = packet.validate();
+            violations.extend(overruns);
+            if padding.iter().any(|bit| *bit != 0) {
+                violations.push(Violation::NonZeroPadding {
+                    position: eaten_bits,
+                });
+            }
+            if !violations.is_empty() {
+                return Err(ValidationError(violations).into());
+            }
         }
+
+        Ok(packet)
     }
 
     pub fn count_versions(&self) -> usize {
-        match &self.packet_type {
-            PacketType::Operator(operator) => self.version as usize + operator.count_versions(),
-            _ => self.version as usize,
-        }
+        self.pre_order().map(|packet| packet.version as usize).sum()
     }
 
     fn to_number(input: &[u8]) -> usize {
@@ -215,84 +449,110 @@ impl Packet {
         number
     }
 
-    fn split_packet(input: &[u8]) -> (Packet, &[u8], usize) {
-        let (version, data) = input.split_at(3);
-        let version = Self::to_number(version) as u8;
-        let (r#type, data) = data.split_at(3);
-        let r#type = Self::to_number(r#type) as u8;
-        let (packet_type, data, taken_bits) = match r#type {
-            4 => {
-                let (literal, data, taken_bits) = Self::split_literal(data);
-                let literal = Self::to_number(&literal);
-                (PacketType::Literal(literal), data, taken_bits)
-            }
-            operator_id => {
-                let (sub_packets, data, taken_bits) = Self::split_operator(data);
-                let operator = Operator {
-                    sub_packets,
-                    id: OperatorType::from(operator_id),
-                };
-                (PacketType::Operator(operator), data, taken_bits)
+    /// splits off the first `amount` bits, or fails when the transmission ends before that
+    fn take(input: &[u8], amount: usize) -> Split<'_, &[u8]> {
+        if input.len() < amount {
+            return Err("transmission ends inside a packet".into());
+        }
+        Ok(input.split_at(amount))
+    }
+
+    /// the packet with the amount of bits it took and the operators whose sub packets ran
+    /// past their declared length
+    fn split_packet(input: &[u8]) -> Split<'_, (Packet, usize, Vec<Violation>)> {
+        let mut stack: Vec<PendingOperator> = Vec::new();
+        let mut overruns = Vec::new();
+        let mut data = input;
+        let mut position = 0;
+
+        loop {
+            if let Some(PendingOperator {
+                remaining: Length::Packets(amount),
+                ..
+            }) = stack.last_mut()
+            {
+                *amount -= 1;
             }
-        };
 
-        (
-            Packet {
-                version,
-                packet_type,
-            },
-            data,
-            6 + taken_bits,
-        )
+            let start = position;
+            let (version, rest) = Self::take(data, 3)?;
+            let version = Self::to_number(version) as u8;
+            let (r#type, rest) = Self::take(rest, 3)?;
+            let r#type = Self::to_number(r#type) as u8;
+            data = rest;
+            position += 6;
+
+            let mut finished = match r#type {
+                4 => {
+                    let ((literal, taken_bits), rest) = Self::split_literal(data)?;
+                    data = rest;
+                    position += taken_bits;
+                    Some(Packet {
+                        version,
+                        packet_type: PacketType::Literal(Self::to_number(&literal)),
+                        bits: position - start,
+                    })
+                }
+                operator_id => {
+                    let (length_type, rest) = Self::take(data, 1)?;
+                    let remaining = if length_type[0] == 1 {
+                        let (length, rest) = Self::take(rest, 11)?;
+                        data = rest;
+                        position += 12;
+                        Length::Packets(Self::to_number(length))
+                    } else {
+                        let (length, rest) = Self::take(rest, 15)?;
+                        data = rest;
+                        position += 16;
+                        Length::Bits(position + Self::to_number(length))
+                    };
+                    stack.push(PendingOperator {
+                        start,
+                        version,
+                        id: OperatorType::try_from(operator_id)?,
+                        remaining,
+                        sub_packets: Vec::new(),
+                    });
+                    None
+                }
+            };
+
+            // hand finished packets to their parent until an operator still expects more
+            loop {
+                if let Some(packet) = finished.take() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.sub_packets.push(packet),
+                        None => return Ok(((packet, position, overruns), data)),
+                    }
+                }
+                match stack.last() {
+                    Some(parent) if parent.is_complete(position) => {
+                        let parent = stack.pop().expect("the parent is on the stack");
+                        overruns.extend(parent.overrun(&stack, position));
+                        finished = Some(parent.into_packet(position))
+                    }
+                    _ => break,
+                }
+            }
+        }
     }
 
-    fn split_literal(input: &[u8]) -> (Vec<u8>, &[u8], usize) {
+    /// the bits of the literal value and the amount of bits it took
+    fn split_literal(input: &[u8]) -> Split<'_, (Vec<u8>, usize)> {
         let mut data = Vec::new();
         let mut has_next = true;
         let mut output = input;
         let mut parts = 0;
         while has_next {
-            let (switch, input) = output.split_at(1);
-            let (literal_part, input) = input.split_at(4);
+            let (switch, input) = Self::take(output, 1)?;
+            let (literal_part, input) = Self::take(input, 4)?;
             output = input;
             has_next = switch[0] == 1;
             data.extend_from_slice(literal_part);
             parts += 1;
         }
 
-        (data, output, parts * 5)
-    }
-
-    fn split_operator(input: &[u8]) -> (Vec<Packet>, &[u8], usize) {
-        let (length_type, input) = input.split_at(1);
-        let (take_bits, absolute) = if length_type[0] == 1 {
-            (11, true)
-        } else {
-            (15, false)
-        };
-        let (length, input) = input.split_at(take_bits);
-        let length = Self::to_number(length);
-        if absolute {
-            let mut total_bits_taken = 0;
-            let mut sub_packets = Vec::new();
-            let mut input = input;
-            for _ in 0..length {
-                let (packet, output, taken_bits) = Self::split_packet(input);
-                total_bits_taken += taken_bits;
-                sub_packets.push(packet);
-                input = output;
-            }
-            (sub_packets, input, 1 + take_bits + total_bits_taken)
-        } else {
-            let (mut sub_packets_bits, input) = input.split_at(length);
-            let mut sub_packets = Vec::new();
-            while !sub_packets_bits.is_empty() {
-                let out = Self::split_packet(sub_packets_bits);
-                sub_packets_bits = out.1;
-                sub_packets.push(out.0);
-            }
-            (sub_packets, input, 1 + take_bits + length)
-        }
+        Ok(((data, parts * 5), output))
     }
 }
 
@@ -315,7 +575,8 @@ fn packet_literal() {
     assert_eq!(
         Packet {
             version: 6,
-            packet_type: PacketType::Literal(2021)
+            packet_type: PacketType::Literal(2021),
+            bits: 21,
         },
         packet
     );
@@ -335,14 +596,17 @@ fn packet_operator2() {
                 sub_packets: vec![
                     Packet {
                         version: 6,
-                        packet_type: PacketType::Literal(10)
+                        packet_type: PacketType::Literal(10),
+                        bits: 11,
                     },
                     Packet {
                         version: 2,
-                        packet_type: PacketType::Literal(20)
+                        packet_type: PacketType::Literal(20),
+                        bits: 16,
                     },
                 ]
-            })
+            }),
+            bits: 49,
         },
         packet
     );
@@ -362,18 +626,22 @@ fn packet_operator3() {
                 sub_packets: vec![
                     Packet {
                         version: 2,
-                        packet_type: PacketType::Literal(1)
+                        packet_type: PacketType::Literal(1),
+                        bits: 11,
                     },
                     Packet {
                         version: 4,
-                        packet_type: PacketType::Literal(2)
+                        packet_type: PacketType::Literal(2),
+                        bits: 11,
                     },
                     Packet {
                         version: 1,
-                        packet_type: PacketType::Literal(3)
+                        packet_type: PacketType::Literal(3),
+                        bits: 11,
                     },
                 ]
-            })
+            }),
+            bits: 51,
         },
         packet
     );
@@ -406,3 +674,63 @@ fn count_versions() {
             .count_versions()
     );
 }
+
+/// the bits of `(number, width)` fields, padded to whole bytes
+#[cfg(test)]
+fn to_bits(fields: &[(usize, usize)]) -> Vec<u8> {
+    let mut bits = Vec::new();
+    for (number, width) in fields {
+        bits.extend((0..*width).rev().map(|i| ((number >> i) & 1) as u8));
+    }
+    while bits.len() % 8 != 0 {
+        bits.push(0);
+    }
+    bits
+}
+
+#[cfg(test)]
+fn to_hex(bits: &[u8]) -> String {
+    bits.chunks(4)
+        .map(|x| format!("{:X}", Packet::to_number(x)))
+        .collect()
+}
+
+#[cfg(test)]
+fn nested_bits(depth: usize) -> Vec<u8> {
+    // version 1, sum operator containing exactly one sub packet
+    let mut fields = [(1, 3), (0, 3), (1, 1), (1, 11)].repeat(depth);
+    // version 2, literal 7
+    fields.extend([(2, 3), (4, 3), (7, 5)]);
+    to_bits(&fields)
+}
+
+#[test]
+fn deeply_nested_packet() {
+    let depth = 100_000;
+    let packet = Packet::from_bytes(&nested_bits(depth)).unwrap();
+
+    assert_eq!(depth + 2, packet.count_versions());
+    assert_eq!(7, packet.value().unwrap());
+    assert_eq!(depth + 1, packet.pre_order().count());
+    assert_eq!(2 * (depth + 1), packet.walk().count());
+
+    let mut evaluator = stream::Evaluator::default();
+    packet.visit(&mut evaluator).unwrap();
+    assert_eq!(Some(7), evaluator.result);
+
+    // validating and searching walk the tree once without copying paths
+    assert!(Packet::from_bytes_with(&nested_bits(depth), Mode::Strict).is_ok());
+    let literals = packet.find(|x| x.is_literal());
+    assert_eq!(vec![vec![0; depth]], literals);
+}
+
+#[test]
+fn walk_order() {
+    let packet = Packet::from_hex("EE00D40C823060").unwrap();
+
+    let pre_order: Vec<_> = packet.pre_order().map(|x| x.version).collect();
+    let post_order: Vec<_> = packet.post_order().map(|x| x.version).collect();
+
+    assert_eq!(vec![7, 2, 4, 1], pre_order);
+    assert_eq!(vec![2, 4, 1, 7], post_order);
+}
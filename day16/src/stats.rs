@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use crate::{OperatorType, Packet, PacketType, WalkEvent};
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Statistics {
+    pub depth: usize,
+    pub packets: usize,
+    pub literals: usize,
+    pub operators: BTreeMap<OperatorType, usize>,
+    pub versions: BTreeMap<u8, usize>,
+    pub largest_literal: Option<usize>,
+    pub bits: usize,
+}
+
+impl Packet {
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(self.packet_type, PacketType::Literal(_))
+    }
+
+    pub fn operator_type(&self) -> Option<OperatorType> {
+        match &self.packet_type {
+            PacketType::Literal(_) => None,
+            PacketType::Operator(operator) => Some(operator.id),
+        }
+    }
+
+    /// amount of bits this packet and its sub packets took in the transmission
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// amount of nesting levels, a lone literal has depth 1
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut max_depth = 0;
+        for event in self.walk() {
+            match event {
+                WalkEvent::Enter(_) => {
+                    depth += 1;
+                    max_depth = max_depth.max(depth);
+                }
+                WalkEvent::Leave(_) => depth -= 1,
+            }
+        }
+        max_depth
+    }
+
+    /// follows the sub packet indices from `Packet::paths` or `Packet::find`
+    pub fn get(&self, path: &[usize]) -> Option<&Packet> {
+        path.iter()
+            .try_fold(self, |packet, index| packet.sub_packets().get(*index))
+    }
+
+    /// paths to all packets matching the predicate, in pre-order
+    pub fn find<F: Fn(&Packet) -> bool>(&self, predicate: F) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        self.for_each_path(|path, packet| {
+            if predicate(packet) {
+                paths.push(path.to_vec());
+            }
+        });
+        paths
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        let mut statistics = Statistics {
+            depth: self.depth(),
+            bits: self.bits,
+            ..Statistics::default()
+        };
+
+        for packet in self.pre_order() {
+            statistics.packets += 1;
+            *statistics.versions.entry(packet.version).or_insert(0) += 1;
+            match &packet.packet_type {
+                PacketType::Literal(value) => {
+                    statistics.literals += 1;
+                    statistics.largest_literal = statistics.largest_literal.max(Some(*value));
+                }
+                PacketType::Operator(operator) => {
+                    *statistics.operators.entry(operator.id).or_insert(0) += 1;
+                }
+            }
+        }
+
+        statistics
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::Statistics;
+    use crate::{OperatorType, Packet};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn statistics_operator() {
+        let packet = Packet::from_hex("EE00D40C823060").unwrap();
+
+        assert_eq!(
+            Statistics {
+                depth: 2,
+                packets: 4,
+                literals: 3,
+                operators: BTreeMap::from([(OperatorType::Maximum, 1)]),
+                versions: BTreeMap::from([(1, 1), (2, 1), (4, 1), (7, 1)]),
+                largest_literal: Some(3),
+                bits: 51,
+            },
+            packet.statistics()
+        );
+    }
+
+    #[test]
+    fn bits_per_subtree() {
+        let packet = Packet::from_hex("38006F45291200").unwrap();
+        let bits: Vec<_> = packet.pre_order().map(|x| x.bits()).collect();
+
+        assert_eq!(vec![49, 11, 16], bits);
+    }
+
+    #[test]
+    fn depth() {
+        assert_eq!(1, Packet::from_hex("D2FE28").unwrap().depth());
+        // operator containing an operator containing an operator containing 5 literals
+        assert_eq!(
+            4,
+            Packet::from_hex("A0016C880162017C3686B18A3D4780")
+                .unwrap()
+                .depth()
+        );
+    }
+
+    #[test]
+    fn find_paths() {
+        // 1 + 3 == 2 * 2
+        let packet = Packet::from_hex("9C0141080250320F1802104A08").unwrap();
+
+        let paths = packet.find(|p| p.is_literal() && p.value().unwrap() > 1);
+        assert_eq!(vec![vec![0, 1], vec![1, 0], vec![1, 1]], paths);
+        assert_eq!(3, packet.get(&paths[0]).unwrap().value().unwrap());
+
+        let paths = packet.find(|p| p.operator_type() == Some(OperatorType::Product));
+        assert_eq!(vec![vec![1]], paths);
+        assert_eq!(None, packet.get(&[2]));
+    }
+}
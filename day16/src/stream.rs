@@ -0,0 +1,434 @@
+use std::io::{BufReader, Bytes, Read};
+
+use crate::{to_usize, OperatorType};
+
+/// Reads bits from a hex encoded transmission one nibble at a time.
+pub struct BitReader<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    nibble: u8,
+    bits_left: u8,
+    position: usize,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(reader: R) -> BitReader<R> {
+        BitReader {
+            bytes: BufReader::new(reader).bytes(),
+            nibble: 0,
+            bits_left: 0,
+            position: 0,
+        }
+    }
+
+    /// amount of bits read so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn next_nibble(&mut self) -> Result<Option<u8>, Box<dyn std::error::Error>> {
+        for byte in self.bytes.by_ref() {
+            let ch = byte? as char;
+            if ch.is_ascii_whitespace() {
+                continue;
+            }
+            let nibble = ch.to_digit(16).ok_or("invalid hexdata")?;
+            return Ok(Some(nibble as u8));
+        }
+        Ok(None)
+    }
+
+    pub fn read_bit(&mut self) -> Result<u8, Box<dyn std::error::Error>> {
+        if self.bits_left == 0 {
+            self.nibble = self.next_nibble()?.ok_or("unexpected end of data")?;
+            self.bits_left = 4;
+        }
+        self.bits_left -= 1;
+        self.position += 1;
+        Ok((self.nibble >> self.bits_left) & 1)
+    }
+
+    pub fn read_bits(&mut self, amount: usize) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut number = 0;
+        for _ in 0..amount {
+            number = (number << 1) | self.read_bit()? as usize;
+        }
+        Ok(number)
+    }
+
+    /// skips the padding up to the next byte boundary and checks that nothing follows
+    pub fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.position.is_multiple_of(8) {
+            self.read_bit()?;
+        }
+        if self.next_nibble()?.is_some() {
+            Err("data remaining".into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Event {
+    EnterOperator { version: u8, id: OperatorType },
+    Literal { version: u8, value: usize },
+    ExitOperator { id: OperatorType },
+}
+
+/// Callbacks for the packets in a transmission, in the order they appear. An error stops
+/// the decoding.
+pub trait Visitor {
+    fn enter_operator(
+        &mut self,
+        _version: u8,
+        _id: OperatorType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn literal(&mut self, _version: u8, _value: usize) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn exit_operator(&mut self, _id: OperatorType) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum Remaining {
+    Bits(usize),
+    Packets(usize),
+}
+
+#[derive(Debug)]
+struct Frame {
+    id: OperatorType,
+    remaining: Remaining,
+}
+
+/// Incremental decoder, only keeps a frame per open operator in memory.
+pub struct Decoder<R: Read> {
+    reader: BitReader<R>,
+    stack: Vec<Frame>,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R) -> Decoder<R> {
+        Decoder {
+            reader: BitReader::new(reader),
+            stack: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    pub fn next_event(&mut self) -> Result<Option<Event>, Box<dyn std::error::Error>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        match self.stack.last_mut() {
+            Some(frame) => match frame.remaining {
+                Remaining::Bits(end) if self.reader.position() == end => {
+                    return Ok(self
+                        .stack
+                        .pop()
+                        .map(|frame| Event::ExitOperator { id: frame.id }));
+                }
+                Remaining::Bits(end) if self.reader.position() > end => {
+                    return Err("sub packets exceed operator length".into());
+                }
+                Remaining::Packets(0) => {
+                    return Ok(self
+                        .stack
+                        .pop()
+                        .map(|frame| Event::ExitOperator { id: frame.id }));
+                }
+                Remaining::Packets(ref mut amount) => *amount -= 1,
+                Remaining::Bits(_) => (),
+            },
+            None if self.started => {
+                self.done = true;
+                self.reader.finish()?;
+                return Ok(None);
+            }
+            None => self.started = true,
+        }
+
+        let version = self.reader.read_bits(3)? as u8;
+        let event = match self.reader.read_bits(3)? as u8 {
+            4 => Event::Literal {
+                version,
+                value: self.read_literal()?,
+            },
+            operator_id => {
+                let id = OperatorType::try_from(operator_id)?;
+                let remaining = if self.reader.read_bit()? == 1 {
+                    Remaining::Packets(self.reader.read_bits(11)?)
+                } else {
+                    let length = self.reader.read_bits(15)?;
+                    Remaining::Bits(self.reader.position() + length)
+                };
+                self.stack.push(Frame { id, remaining });
+                Event::EnterOperator { version, id }
+            }
+        };
+
+        Ok(Some(event))
+    }
+
+    fn read_literal(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut value: usize = 0;
+        loop {
+            let has_next = self.reader.read_bit()? == 1;
+            let part = self.reader.read_bits(4)?;
+            value = value
+                .checked_mul(16)
+                .ok_or("literal does not fit in usize")?
+                | part;
+            if !has_next {
+                return Ok(value);
+            }
+        }
+    }
+
+    pub fn decode<V: Visitor>(mut self, visitor: &mut V) -> Result<(), Box<dyn std::error::Error>> {
+        while let Some(event) = self.next_event()? {
+            match event {
+                Event::EnterOperator { version, id } => visitor.enter_operator(version, id)?,
+                Event::Literal { version, value } => visitor.literal(version, value)?,
+                Event::ExitOperator { id } => visitor.exit_operator(id)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<Event, Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VersionCounter {
+    pub total: usize,
+}
+
+impl Visitor for VersionCounter {
+    fn enter_operator(
+        &mut self,
+        version: u8,
+        _id: OperatorType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.total += version as usize;
+        Ok(())
+    }
+
+    fn literal(&mut self, version: u8, _value: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.total += version as usize;
+        Ok(())
+    }
+}
+
+/// Evaluates the transmission keeping the values of the finished sub packets of the open
+/// operators.
+#[derive(Debug, Default)]
+pub struct Evaluator {
+    values: Vec<u128>,
+    /// where the values of each open operator start
+    starts: Vec<usize>,
+    pub result: Option<usize>,
+}
+
+impl Evaluator {
+    fn push(&mut self, value: u128) -> Result<(), Box<dyn std::error::Error>> {
+        if self.starts.is_empty() {
+            self.result = Some(to_usize(value)?);
+        } else {
+            self.values.push(value);
+        }
+        Ok(())
+    }
+}
+
+impl Visitor for Evaluator {
+    fn enter_operator(
+        &mut self,
+        _version: u8,
+        _id: OperatorType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.starts.push(self.values.len());
+        Ok(())
+    }
+
+    fn literal(&mut self, _version: u8, value: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.push(value as u128)
+    }
+
+    fn exit_operator(&mut self, id: OperatorType) -> Result<(), Box<dyn std::error::Error>> {
+        let start = self.starts.pop().ok_or("exit without matching enter")?;
+        let value = id.apply_values(&self.values[start..])?;
+        self.values.truncate(start);
+        self.push(value)
+    }
+}
+
+pub fn count_versions<R: Read>(reader: R) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut counter = VersionCounter::default();
+    Decoder::new(reader).decode(&mut counter)?;
+    Ok(counter.total)
+}
+
+pub fn evaluate<R: Read>(reader: R) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut evaluator = Evaluator::default();
+    Decoder::new(reader).decode(&mut evaluator)?;
+    Ok(evaluator.result.ok_or("empty transmission")?)
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use super::{count_versions, evaluate, Decoder, Event};
+    use crate::{to_bits, to_hex, OperatorType};
+
+    /// a version 0 operator counting its sub packets
+    fn operator(id: usize, sub_packets: &[Vec<(usize, usize)>]) -> Vec<(usize, usize)> {
+        let mut fields = vec![(0, 3), (id, 3), (1, 1), (sub_packets.len(), 11)];
+        fields.extend(sub_packets.iter().flatten());
+        fields
+    }
+
+    /// a version 0 literal
+    fn literal(value: usize) -> Vec<(usize, usize)> {
+        let mut nibbles: Vec<_> = (0..usize::BITS as usize / 4)
+            .rev()
+            .map(|i| (value >> (4 * i)) & 0xF)
+            .skip_while(|x| *x == 0)
+            .collect();
+        if nibbles.is_empty() {
+            nibbles.push(0);
+        }
+        let mut fields = vec![(0, 3), (4, 3)];
+        for (i, nibble) in nibbles.iter().enumerate() {
+            fields.push(((i + 1 < nibbles.len()) as usize, 1));
+            fields.push((*nibble, 4));
+        }
+        fields
+    }
+
+    fn evaluate_fields(fields: &[(usize, usize)]) -> Result<usize, Box<dyn std::error::Error>> {
+        evaluate(to_hex(&to_bits(fields)).as_bytes())
+    }
+
+    #[test]
+    fn events_literal() {
+        let events: Vec<_> = Decoder::new("D2FE28".as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            vec![Event::Literal {
+                version: 6,
+                value: 2021
+            }],
+            events
+        );
+    }
+
+    #[test]
+    fn events_operator() {
+        let events: Vec<_> = Decoder::new("38006F45291200".as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                Event::EnterOperator {
+                    version: 1,
+                    id: OperatorType::LessThan
+                },
+                Event::Literal {
+                    version: 6,
+                    value: 10
+                },
+                Event::Literal {
+                    version: 2,
+                    value: 20
+                },
+                Event::ExitOperator {
+                    id: OperatorType::LessThan
+                },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn streaming_count_versions() {
+        assert_eq!(16, count_versions("8A004A801A8002F478".as_bytes()).unwrap());
+        assert_eq!(
+            12,
+            count_versions("620080001611562C8802118E34".as_bytes()).unwrap()
+        );
+        assert_eq!(
+            23,
+            count_versions("C0015000016115A2E0802F182340".as_bytes()).unwrap()
+        );
+        assert_eq!(
+            31,
+            count_versions("A0016C880162017C3686B18A3D4780\n".as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn streaming_evaluate() {
+        assert_eq!(3, evaluate("C200B40A82".as_bytes()).unwrap());
+        assert_eq!(54, evaluate("04005AC33890".as_bytes()).unwrap());
+        assert_eq!(7, evaluate("880086C3E88112".as_bytes()).unwrap());
+        assert_eq!(9, evaluate("CE00C43D881120".as_bytes()).unwrap());
+        assert_eq!(1, evaluate("D8005AC2A8F0".as_bytes()).unwrap());
+        assert_eq!(0, evaluate("F600BC2D8F".as_bytes()).unwrap());
+        assert_eq!(0, evaluate("9C005AC2F8F0".as_bytes()).unwrap());
+        assert_eq!(
+            1,
+            evaluate("9C0141080250320F1802104A08".as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn streaming_errors() {
+        assert!(evaluate("D2FE2800".as_bytes()).is_err());
+        assert!(evaluate("D2FE".as_bytes()).is_err());
+        assert!(evaluate("D2FEXX".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn malformed_operators() {
+        // equal with a single sub packet, and greater than with three
+        let lone = operator(7, &[literal(1)]);
+        let three = operator(5, &[literal(3), literal(2), literal(1)]);
+        let pair = operator(5, &[literal(3), literal(2)]);
+
+        assert_eq!(1, evaluate_fields(&pair).unwrap());
+        assert!(evaluate_fields(&lone).is_err());
+        assert!(evaluate_fields(&three).is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        let sum = operator(0, &[literal(usize::MAX), literal(1)]);
+        let product = operator(1, &[literal(1 << 40), literal(1 << 40)]);
+
+        assert_eq!(
+            usize::MAX,
+            evaluate_fields(&operator(0, &[literal(usize::MAX)])).unwrap()
+        );
+        assert!(evaluate_fields(&sum).is_err());
+        assert!(evaluate_fields(&product).is_err());
+    }
+}
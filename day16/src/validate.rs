@@ -0,0 +1,288 @@
+use std::fmt;
+
+use crate::{OperatorType, Packet, PacketType};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    /// reject transmissions with any violation
+    Strict,
+    /// only reject transmissions that cannot be decoded at all
+    Lenient,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Violation {
+    Arity {
+        path: Vec<usize>,
+        id: OperatorType,
+        sub_packets: usize,
+    },
+    EmptyOperator {
+        path: Vec<usize>,
+        id: OperatorType,
+    },
+    NonZeroPadding {
+        position: usize,
+    },
+    /// the sub packets end at bit `position`, after the `end` the operator declared
+    LengthOverrun {
+        path: Vec<usize>,
+        id: OperatorType,
+        end: usize,
+        position: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Violation::*;
+        match self {
+            Arity {
+                path,
+                id,
+                sub_packets,
+            } => write!(
+                f,
+                "{:?} at {:?} expects two sub packets, got {}",
+                id, path, sub_packets
+            ),
+            EmptyOperator { path, id } => write!(f, "{:?} at {:?} has no sub packets", id, path),
+            NonZeroPadding { position } => write!(f, "non zero padding after bit {}", position),
+            LengthOverrun {
+                path,
+                id,
+                end,
+                position,
+            } => write!(
+                f,
+                "{:?} at {:?} declares sub packets up to bit {}, they end at bit {}",
+                id, path, end, position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+#[derive(Debug, PartialEq)]
+pub struct ValidationError(pub Vec<Violation>);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let messages: Vec<_> = self.0.iter().map(|x| x.to_string()).collect();
+        write!(f, "invalid transmission: {}", messages.join(", "))
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Packet {
+    /// Checks the arity of comparisons and that other operators are not empty. Padding and
+    /// operator lengths are checked by `from_bytes_with` in `Mode::Strict`.
+    pub fn validate(&self) -> Vec<Violation> {
+        use OperatorType::*;
+
+        let mut violations = Vec::new();
+        self.for_each_path(|path, packet| {
+            let operator = match &packet.packet_type {
+                PacketType::Operator(operator) => operator,
+                PacketType::Literal(_) => return,
+            };
+            let sub_packets = operator.sub_packets.len();
+            match operator.id {
+                Sum | Product | Minimum | Maximum if sub_packets == 0 => {
+                    violations.push(Violation::EmptyOperator {
+                        path: path.to_vec(),
+                        id: operator.id,
+                    })
+                }
+                GreaterThan | LessThan | Equal if sub_packets != 2 => {
+                    violations.push(Violation::Arity {
+                        path: path.to_vec(),
+                        id: operator.id,
+                        sub_packets,
+                    })
+                }
+                _ => (),
+            }
+        });
+        violations
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::{Mode, ValidationError, Violation};
+    use crate::{to_bits, OperatorType, Packet};
+
+    fn violations(bits: &[u8]) -> Vec<Violation> {
+        Packet::from_bytes_with(bits, Mode::Strict)
+            .unwrap_err()
+            .downcast::<ValidationError>()
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn valid_examples() {
+        for data in [
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "9C0141080250320F1802104A08",
+        ] {
+            let packet = Packet::from_hex_with(data, Mode::Strict).unwrap();
+            assert!(packet.validate().is_empty());
+        }
+    }
+
+    #[test]
+    fn non_zero_padding() {
+        assert!(Packet::from_hex_with("D2FE29", Mode::Lenient).is_ok());
+
+        let error = Packet::from_hex_with("D2FE29", Mode::Strict).unwrap_err();
+        assert_eq!(
+            &ValidationError(vec![Violation::NonZeroPadding { position: 21 }]),
+            error.downcast_ref::<ValidationError>().unwrap()
+        );
+    }
+
+    #[test]
+    fn empty_operator() {
+        // sum operator with zero sub packets
+        let bits = to_bits(&[(0, 3), (0, 3), (1, 1), (0, 11)]);
+
+        assert_eq!(0, Packet::from_bytes(&bits).unwrap().value().unwrap());
+        assert_eq!(
+            vec![Violation::EmptyOperator {
+                path: vec![],
+                id: OperatorType::Sum
+            }],
+            violations(&bits)
+        );
+    }
+
+    #[test]
+    fn comparison_arity() {
+        // sum of a greater than with three literals and a lone equal
+        let bits = to_bits(&[
+            (0, 3),
+            (0, 3),
+            (1, 1),
+            (2, 11),
+            (0, 3),
+            (5, 3),
+            (1, 1),
+            (3, 11),
+            (0, 3),
+            (4, 3),
+            (3, 5),
+            (0, 3),
+            (4, 3),
+            (2, 5),
+            (0, 3),
+            (4, 3),
+            (9, 5),
+            (0, 3),
+            (7, 3),
+            (1, 1),
+            (1, 11),
+            (0, 3),
+            (4, 3),
+            (1, 5),
+        ]);
+
+        assert_eq!(
+            vec![
+                Violation::Arity {
+                    path: vec![0],
+                    id: OperatorType::GreaterThan,
+                    sub_packets: 3
+                },
+                Violation::Arity {
+                    path: vec![1],
+                    id: OperatorType::Equal,
+                    sub_packets: 1
+                }
+            ],
+            violations(&bits)
+        );
+        // a lenient parse keeps them, evaluating fails
+        assert!(Packet::from_bytes(&bits).unwrap().value().is_err());
+    }
+
+    #[test]
+    fn overflowing_value() {
+        // product of 2^40 and 2^40, and the sum of usize::MAX and 1 as literals with 16 groups
+        let huge = |value: usize| {
+            let mut fields = vec![(0, 3), (4, 3)];
+            for i in (0..16).rev() {
+                fields.push(((i > 0) as usize, 1));
+                fields.push(((value >> (4 * i)) & 0xF, 4));
+            }
+            fields
+        };
+        let operator = |id: usize, values: [usize; 2]| {
+            let mut fields = vec![(0, 3), (id, 3), (1, 1), (2, 11)];
+            fields.extend(values.into_iter().flat_map(huge));
+            to_bits(&fields)
+        };
+
+        let fits = Packet::from_bytes(&operator(1, [1 << 20, 1 << 20])).unwrap();
+        assert_eq!(1 << 40, fits.value().unwrap());
+        let product = Packet::from_bytes(&operator(1, [1 << 40, 1 << 40])).unwrap();
+        assert!(product.value().is_err());
+        let sum = Packet::from_bytes(&operator(0, [usize::MAX, 1])).unwrap();
+        assert!(sum.value().is_err());
+    }
+
+    #[test]
+    fn length_overrun() {
+        // a sum declaring 5 bits of sub packets around an 11 bit literal
+        let bits = to_bits(&[(0, 3), (0, 3), (0, 1), (5, 15), (0, 3), (4, 3), (7, 5)]);
+
+        assert_eq!(7, Packet::from_bytes(&bits).unwrap().value().unwrap());
+        assert_eq!(
+            vec![Violation::LengthOverrun {
+                path: vec![],
+                id: OperatorType::Sum,
+                end: 27,
+                position: 33
+            }],
+            violations(&bits)
+        );
+
+        // the same operator one level down, inside a sum counting its sub packets
+        let nested = to_bits(&[
+            (0, 3),
+            (0, 3),
+            (1, 1),
+            (1, 11),
+            (0, 3),
+            (0, 3),
+            (0, 1),
+            (5, 15),
+            (0, 3),
+            (4, 3),
+            (7, 5),
+        ]);
+        assert_eq!(
+            vec![Violation::LengthOverrun {
+                path: vec![0],
+                id: OperatorType::Sum,
+                end: 45,
+                position: 51
+            }],
+            violations(&nested)
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        for mode in [Mode::Strict, Mode::Lenient] {
+            assert!(Packet::from_hex_with("D2FE", mode).is_err());
+            assert!(Packet::from_hex_with("38006F4529", mode).is_err());
+            assert!(Packet::from_hex_with("", mode).is_err());
+        }
+    }
+}
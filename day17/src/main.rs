@@ -1,10 +1,14 @@
 use std::fs::read_to_string;
-use std::ops::{Bound, RangeBounds, RangeInclusive};
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 
+use aabox::{AaBox, Range};
 use derive_more::{Deref, DerefMut, From};
 
-pub type Range = RangeInclusive<i32>;
+pub mod trajectory;
+
+use trajectory::peak;
+
 pub type Pair = (i32, i32);
 
 fn bound_to_options(b: Bound<&i32>) -> Option<&i32> {
@@ -17,56 +21,53 @@ fn bound_to_options(b: Bound<&i32>) -> Option<&i32> {
 
 #[derive(Debug, PartialEq)]
 pub struct Area {
-    range_x: Range,
-    range_y: Range,
+    target: AaBox<2>,
 }
 
 impl Area {
     pub fn from_txt(input: &str) -> Result<Area, Box<dyn std::error::Error>> {
-        let (x_range_txt, y_range_txt) = if let Some((x, y)) = input
-            .trim_start_matches("target area: ")
-            .trim_end()
-            .split_once(", ")
-        {
-            let x = x.trim_start_matches("x=");
-            let y = y.trim_start_matches("y=");
-            (x, y)
-        } else {
-            return Err("invalid input".into());
-        };
-
-        let x_range = Self::range_txt_to_range(x_range_txt)?;
-        let y_range = Self::range_txt_to_range(y_range_txt)?;
-        Ok(Area::new(x_range, y_range))
+        Ok(Area {
+            target: AaBox::parse(input.trim_start_matches("target area: "))?,
+        })
     }
 
     pub fn new(range_x: Range, range_y: Range) -> Area {
-        Area { range_x, range_y }
+        Area {
+            target: AaBox::new([range_x, range_y]),
+        }
+    }
+
+    pub fn range_x(&self) -> &Range {
+        self.target.range(0)
+    }
+
+    pub fn range_y(&self) -> &Range {
+        self.target.range(1)
     }
 
     // on target
     pub fn on(&self, pair: &Pair) -> bool {
-        self.range_x.contains(&pair.0) && self.range_y.contains(&pair.1)
+        self.target.contains(&[pair.0, pair.1])
     }
 
     /// area under and after the 'area'
     pub fn through(&self, pair: &Pair) -> bool {
-        &pair.0 >= bound_to_options(self.range_x.start_bound()).unwrap()
-            && &pair.1 <= bound_to_options(self.range_y.end_bound()).unwrap()
+        &pair.0 >= bound_to_options(self.range_x().start_bound()).unwrap()
+            && &pair.1 <= bound_to_options(self.range_y().end_bound()).unwrap()
             && !self.on(pair)
     }
 
     /// area up and before the 'area'
     pub fn before(&self, pair: &Pair) -> bool {
-        &pair.0 <= bound_to_options(self.range_x.end_bound()).unwrap()
-            && &pair.1 >= bound_to_options(self.range_y.start_bound()).unwrap()
+        &pair.0 <= bound_to_options(self.range_x().end_bound()).unwrap()
+            && &pair.1 >= bound_to_options(self.range_y().start_bound()).unwrap()
             && !self.on(pair)
     }
 
     /// area under and/or before, and after and/or up the 'area'
     pub fn out(&self, pair: &Pair) -> bool {
-        &pair.0 > bound_to_options(self.range_x.end_bound()).unwrap()
-            || &pair.1 < bound_to_options(self.range_y.start_bound()).unwrap()
+        &pair.0 > bound_to_options(self.range_x().end_bound()).unwrap()
+            || &pair.1 < bound_to_options(self.range_y().start_bound()).unwrap()
     }
 
     pub fn get_target_route(&self, acceleration: Pair) -> Option<Vec<Pair>> {
@@ -85,20 +86,6 @@ impl Area {
 
         None
     }
-
-    fn range_txt_to_range(input: &str) -> Result<Range, Box<dyn std::error::Error>> {
-        if let Some(range) = input.split_once("..").map(
-            |(start, end)| -> Result<Range, Box<dyn std::error::Error>> {
-                let start: i32 = start.parse()?;
-                let end: i32 = end.parse()?;
-                Ok(start..=end)
-            },
-        ) {
-            range
-        } else {
-            Err("invalid range".into())
-        }
-    }
 }
 
 #[derive(Debug, PartialEq, Clone, Deref, DerefMut, From)]
@@ -151,10 +138,6 @@ impl Iterator for Probe {
     }
 }
 
-fn bound_to_search_range(bound: Bound<&i32>) -> i32 {
-    bound_to_options(bound).unwrap().abs() + 1
-}
-
 fn fetch_file_path() -> &'static str {
     if Path::new("src/input.txt").exists() {
         "src/input.txt"
@@ -171,30 +154,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn part_one(input_path: &str) -> Result<i32, Box<dyn std::error::Error>> {
+fn part_one(input_path: &str) -> Result<i64, Box<dyn std::error::Error>> {
     let data = read_to_string(input_path)?;
     let area = Area::from_txt(&data)?;
 
-    let mut routes = Vec::new();
-
-    let a = bound_to_search_range(area.range_x.end_bound());
-    let b = bound_to_search_range(area.range_y.start_bound());
-
-    for (i, j) in (0..a).flat_map(|x| (0..b).map(move |y| (x, y))) {
-        if let Some(route) = area.get_target_route((i, j)) {
-            routes.push(route)
-        }
-    }
-
-    let max_route = routes
+    let max_y = area
+        .velocities()?
         .iter()
-        .max_by_key(|x| x.iter().max_by_key(|y| y.1))
-        .expect("routes not found");
-    let max_y_position = max_route
-        .iter()
-        .max_by_key(|y| y.1)
-        .expect("max route is empty");
-    let max_y = max_y_position.1;
+        .map(peak)
+        .max()
+        .ok_or("no velocity reaches the area")?;
 
     Ok(max_y)
 }
@@ -203,24 +172,22 @@ fn part_two(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
     let data = read_to_string(input_path)?;
     let area = Area::from_txt(&data)?;
 
-    let mut routes = Vec::new();
-
-    let a = bound_to_search_range(area.range_x.end_bound());
-    let b = bound_to_search_range(area.range_y.start_bound());
-
-    for (i, j) in (-a..a).flat_map(|x| (-b..b).map(move |y| (x, y))) {
-        if let Some(route) = area.get_target_route((i, j)) {
-            routes.push(route)
-        }
-    }
-
-    Ok(routes.len())
+    Ok(area.velocities()?.len())
 }
 
 #[cfg(test)]
 mod area_test {
     use super::Area;
 
+    #[test]
+    fn from_txt() {
+        assert_eq!(
+            Area::new(20..=30, -10..=-5),
+            Area::from_txt("target area: x=20..30, y=-10..-5\n").unwrap()
+        );
+        assert!(Area::from_txt("target area: x=20..30").is_err());
+    }
+
     #[test]
     fn on() {
         let area = Area::new(0..=3, 8..=11);
@@ -0,0 +1,235 @@
+use std::collections::BTreeSet;
+
+use aabox::Range;
+
+use crate::{Area, Pair};
+
+// the sums are done in i64, triangular numbers of i32 coordinates do not fit in i32
+fn triangular(n: i64) -> i64 {
+    n * (n + 1) / 2
+}
+
+/// largest `n` with `triangular(n) <= x`, `x` can not be negative
+fn triangular_root(x: i64) -> i64 {
+    let mut n = ((((8 * x + 1) as f64).sqrt() - 1.0) / 2.0) as i64;
+    while triangular(n + 1) <= x {
+        n += 1;
+    }
+    while triangular(n) > x {
+        n -= 1;
+    }
+    n
+}
+
+fn ceil_div(a: i64, b: i64) -> i64 {
+    -(-a).div_euclid(b)
+}
+
+fn empty() -> Range {
+    Range::new(1, 0)
+}
+
+/// the velocities of the range that fit in i32
+fn to_range(start: i64, end: i64) -> Range {
+    let clamp = |x: i64| x.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+    if start > end {
+        empty()
+    } else {
+        clamp(start)..=clamp(end)
+    }
+}
+
+/// smallest range that holds all the non empty ranges
+fn hull(ranges: &[Range]) -> Range {
+    let ranges: Vec<_> = ranges.iter().filter(|x| !x.is_empty()).collect();
+    match (
+        ranges.iter().map(|x| x.start()).min(),
+        ranges.iter().map(|x| x.end()).max(),
+    ) {
+        (Some(start), Some(end)) => *start..=*end,
+        _ => empty(),
+    }
+}
+
+/// positive x velocities that are between `start` and `end` after exactly `steps` steps
+fn forward_velocities(start: i64, end: i64, steps: i64) -> Range {
+    if end < 0 {
+        return empty();
+    }
+    let start = start.max(0);
+    let travelled = triangular(steps - 1);
+
+    // a velocity below the amount of steps stopped at its triangular number
+    let lowest = if start == 0 {
+        0
+    } else {
+        triangular_root(start - 1) + 1
+    };
+    let stopped = to_range(lowest, triangular_root(end).min(steps - 1));
+    // the others still move and are at `steps * velocity - triangular(steps - 1)`
+    let moving = to_range(
+        ceil_div(start + travelled, steps).max(steps),
+        (end + travelled).div_euclid(steps),
+    );
+
+    // the position after a fixed amount of steps grows with the velocity, so both are adjacent
+    hull(&[stopped, moving])
+}
+
+/// highest y a probe launched with the velocity reaches
+pub fn peak(velocity: &Pair) -> i64 {
+    if velocity.1 > 0 {
+        triangular(velocity.1 as i64)
+    } else {
+        velocity.1 as i64
+    }
+}
+
+impl Area {
+    /// x velocities that are on target after exactly `steps` steps
+    pub fn velocities_x(&self, steps: i64) -> Range {
+        let (start, end) = (*self.range_x().start() as i64, *self.range_x().end() as i64);
+        let backward = forward_velocities(-end, -start, steps);
+        hull(&[
+            forward_velocities(start, end, steps),
+            -backward.end()..=-backward.start(),
+        ])
+    }
+
+    /// y velocities that are on target after exactly `steps` steps
+    pub fn velocities_y(&self, steps: i64) -> Range {
+        let (start, end) = (*self.range_y().start() as i64, *self.range_y().end() as i64);
+        // the position is `steps * velocity - triangular(steps - 1)`
+        let travelled = triangular(steps - 1);
+        to_range(
+            ceil_div(start + travelled, steps),
+            (end + travelled).div_euclid(steps),
+        )
+    }
+
+    /// every velocity that is on target after some step, the area has to be below the launcher
+    pub fn velocities(&self) -> Result<BTreeSet<Pair>, Box<dyn std::error::Error>> {
+        let bottom = *self.range_y().start() as i64;
+        if *self.range_y().end() >= 0 {
+            return Err("the area has to be below the launcher".into());
+        }
+
+        // the highest probe comes back to zero with a velocity of `bottom` and hits it right after
+        let mut velocities = BTreeSet::new();
+        for steps in 1..=-2 * bottom {
+            let range_y = self.velocities_y(steps);
+            for x in self.velocities_x(steps) {
+                velocities.extend(range_y.clone().map(|y| (x, y)));
+            }
+        }
+        Ok(velocities)
+    }
+}
+
+#[cfg(test)]
+mod trajectory_tests {
+    use super::{peak, triangular, triangular_root};
+    use crate::{fetch_file_path, Area, Pair, Probe};
+    use std::collections::BTreeSet;
+    use std::fs::read_to_string;
+    use std::time::Instant;
+
+    /// tries every velocity that could reach the area with the simulator
+    fn brute_force(area: &Area) -> BTreeSet<Pair> {
+        let (start_x, end_x) = (*area.range_x().start(), *area.range_x().end());
+        let bottom = *area.range_y().start();
+        let hits = |velocity: Pair| {
+            Probe::new(velocity)
+                .take_while(|x| x.1 >= bottom)
+                .any(|x| area.on(&x.position))
+        };
+
+        (start_x.min(0)..=end_x.max(0))
+            .flat_map(|x| (bottom..-bottom).map(move |y| (x, y)))
+            .filter(|x| hits(*x))
+            .collect()
+    }
+
+    fn input_area() -> Area {
+        Area::from_txt(&read_to_string(fetch_file_path()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn triangular_roots() {
+        for x in 0..200 {
+            let n = triangular_root(x);
+            assert!(triangular(n) <= x && x < triangular(n + 1), "{}", x);
+        }
+    }
+
+    #[test]
+    fn velocities_per_step() {
+        let area = Area::new(20..=30, -10..=-5);
+
+        assert_eq!(20..=30, area.velocities_x(1));
+        assert_eq!(-10..=-5, area.velocities_y(1));
+        assert_eq!(6..=7, area.velocities_x(7));
+        assert_eq!(2..=2, area.velocities_y(7));
+        // 6 and 7 stop in the area, faster ones have passed it
+        assert_eq!(6..=7, area.velocities_x(100));
+
+        let mirrored = Area::new(-30..=-20, -10..=-5);
+        assert_eq!(-7..=-6, mirrored.velocities_x(7));
+    }
+
+    #[test]
+    fn example() {
+        let area = Area::new(20..=30, -10..=-5);
+        let velocities = area.velocities().unwrap();
+
+        assert_eq!(112, velocities.len());
+        assert_eq!(Some(45), velocities.iter().map(peak).max());
+        assert!(velocities.contains(&(7, 2)) && velocities.contains(&(6, 9)));
+    }
+
+    #[test]
+    fn same_as_simulator() {
+        for area in [
+            input_area(),
+            Area::new(20..=30, -10..=-5),
+            Area::new(-30..=-20, -10..=-5),
+            Area::new(-3..=4, -8..=-2),
+            Area::new(0..=0, -1..=-1),
+        ] {
+            assert_eq!(brute_force(&area), area.velocities().unwrap(), "{:?}", area);
+        }
+    }
+
+    #[test]
+    fn far_area() {
+        let velocities = Area::new(20..=30, -60000..=-50000).velocities().unwrap();
+
+        assert_eq!(Some(triangular(59999)), velocities.iter().map(peak).max());
+        assert!(velocities.contains(&(20, -60000)) && velocities.contains(&(6, 59999)));
+    }
+
+    #[test]
+    fn area_above() {
+        assert!(Area::new(20..=30, 5..=10).velocities().is_err());
+        assert!(Area::new(20..=30, -5..=0).velocities().is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_brute_force_vs_analytic() {
+        let area = input_area();
+
+        let solvers = [
+            ("brute force", brute_force as fn(&Area) -> BTreeSet<Pair>),
+            ("analytic", |area| area.velocities().unwrap()),
+        ];
+        let mut velocities = Vec::new();
+        for (label, solve) in solvers {
+            let start = Instant::now();
+            velocities.push(solve(&area));
+            println!("{}: {:?}", label, start.elapsed());
+        }
+
+        assert_eq!(velocities[0], velocities[1]);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{Block, ReactorCore, Triple};
+
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Bounds {
+    min: [i32; 3],
+    max: [i32; 3],
+}
+
+impl Bounds {
+    fn of(block: &Block) -> Bounds {
+        Bounds {
+            min: block.cuboid.min(),
+            max: block.cuboid.max(),
+        }
+    }
+
+    fn merge(&self, other: &Bounds) -> Bounds {
+        let mut merged = *self;
+        for axis in 0..3 {
+            merged.min[axis] = merged.min[axis].min(other.min[axis]);
+            merged.max[axis] = merged.max[axis].max(other.max[axis]);
+        }
+        merged
+    }
+
+    fn contains(&self, (x, y, z): &Triple) -> bool {
+        [*x, *y, *z]
+            .iter()
+            .enumerate()
+            .all(|(axis, value)| self.min[axis] <= *value && *value <= self.max[axis])
+    }
+
+    fn overlaps(&self, other: &Bounds) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// doubled center, to stay in integers
+    fn center(&self, axis: usize) -> i64 {
+        self.min[axis] as i64 + self.max[axis] as i64
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Leaf {
+        bounds: Bounds,
+        blocks: Vec<usize>,
+    },
+    Inner {
+        bounds: Bounds,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Bounds {
+        match self {
+            Node::Leaf { bounds, .. } => bounds,
+            Node::Inner { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// Bounding volume hierarchy over the blocks of a reactor core, remembers the order of the
+/// blocks so the last block containing a point still decides if it is on.
+#[derive(Debug, PartialEq)]
+pub struct BlockIndex {
+    blocks: Vec<Block>,
+    nodes: Vec<Node>,
+}
+
+impl BlockIndex {
+    pub fn new(blocks: Vec<Block>) -> BlockIndex {
+        let mut index = BlockIndex {
+            blocks,
+            nodes: Vec::new(),
+        };
+        if !index.blocks.is_empty() {
+            let all = (0..index.blocks.len()).collect();
+            index.build(all);
+        }
+        index
+    }
+
+    /// adds the node for the given blocks and its children, returns its position
+    fn build(&mut self, mut blocks: Vec<usize>) -> usize {
+        let bounds = blocks
+            .iter()
+            .map(|x| Bounds::of(&self.blocks[*x]))
+            .reduce(|a, b| a.merge(&b))
+            .expect("nodes are never empty");
+
+        if blocks.len() <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf { bounds, blocks });
+            return self.nodes.len() - 1;
+        }
+
+        // split at the median center along the longest axis
+        let axis = (0..3)
+            .max_by_key(|axis| bounds.max[*axis] as i64 - bounds.min[*axis] as i64)
+            .expect("there are three axes");
+        blocks.sort_by_key(|x| Bounds::of(&self.blocks[*x]).center(axis));
+        let right_blocks = blocks.split_off(blocks.len() / 2);
+
+        let position = self.nodes.len();
+        self.nodes.push(Node::Leaf {
+            bounds,
+            blocks: Vec::new(),
+        });
+        let left = self.build(blocks);
+        let right = self.build(right_blocks);
+        self.nodes[position] = Node::Inner {
+            bounds,
+            left,
+            right,
+        };
+        position
+    }
+
+    /// positions of the blocks whose bounds pass the filter, in the order they were given
+    fn search<F: Fn(&Bounds) -> bool>(&self, filter: F) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(position) = stack.pop() {
+            let node = &self.nodes[position];
+            if !filter(node.bounds()) {
+                continue;
+            }
+            match node {
+                Node::Leaf { blocks, .. } => found.extend(
+                    blocks
+                        .iter()
+                        .filter(|x| filter(&Bounds::of(&self.blocks[**x]))),
+                ),
+                Node::Inner { left, right, .. } => stack.extend([*left, *right]),
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+
+    pub fn on(&self, triple: &Triple) -> bool {
+        self.search(|bounds| bounds.contains(triple))
+            .last()
+            .is_some_and(|x| self.blocks[*x].is_on)
+    }
+
+    /// blocks overlapping the region, in the order they are applied
+    pub fn covering(&self, region: &Block) -> Vec<&Block> {
+        let region = Bounds::of(region);
+        self.search(|bounds| bounds.overlaps(&region))
+            .into_iter()
+            .map(|x| &self.blocks[x])
+            .collect()
+    }
+}
+
+impl ReactorCore {
+    pub fn index(&self) -> BlockIndex {
+        BlockIndex::new(self.blocks.clone())
+    }
+}
+
+#[cfg(test)]
+mod index_tests {
+    use super::BlockIndex;
+    use crate::{fetch_file_path, Block, ReactorCore};
+    use std::fs::read_to_string;
+
+    fn input_core() -> ReactorCore {
+        ReactorCore::from_text(&read_to_string(fetch_file_path()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn on_matches_linear_scan() {
+        let core = input_core();
+        let index = core.index();
+
+        // the corners of every block and the cubes just outside them
+        for block in core.blocks.iter() {
+            let (min, max) = (block.cuboid.min(), block.cuboid.max());
+            let edges = |axis: usize| [min[axis] - 1, min[axis], max[axis], max[axis] + 1];
+            for x in edges(0) {
+                for y in edges(1) {
+                    for z in edges(2) {
+                        let point = (x, y, z);
+                        assert_eq!(core.on(&point), index.on(&point), "{:?}", point);
+                    }
+                }
+            }
+        }
+        // and the initialization region
+        for x in (-50..=50).step_by(5) {
+            for y in (-50..=50).step_by(5) {
+                for z in (-50..=50).step_by(5) {
+                    let point = (x, y, z);
+                    assert_eq!(core.on(&point), index.on(&point), "{:?}", point);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn covering_matches_linear_scan() {
+        let core = input_core();
+        let index = core.index();
+
+        for region in [
+            Block::new(-50..=50, -50..=50, -50..=50, true),
+            Block::new(0..=0, 0..=0, 0..=0, true),
+            Block::new(10_000..=40_000, -20_000..=0, 5..=100_000, true),
+            Block::new(200_000..=300_000, 0..=1, 0..=1, true),
+        ] {
+            let expected: Vec<_> = core
+                .blocks
+                .iter()
+                .filter(|x| x.intersect(&region, true).is_some())
+                .collect();
+            assert_eq!(expected, index.covering(&region));
+        }
+    }
+
+    #[test]
+    fn empty_index() {
+        let index = BlockIndex::new(Vec::new());
+
+        assert!(!index.on(&(0, 0, 0)));
+        assert!(index
+            .covering(&Block::new(0..=1, 0..=1, 0..=1, true))
+            .is_empty());
+    }
+}
@@ -1,10 +1,16 @@
-use rayon::iter::IntoParallelIterator;
-use rayon::iter::ParallelIterator;
 use std::fs::read_to_string;
-use std::ops::RangeInclusive;
 use std::path::Path;
 
-pub type Range = RangeInclusive<i32>;
+use aabox::AaBox;
+
+pub mod index;
+pub mod mesh;
+pub mod timeline;
+pub mod volume;
+
+use volume::{DisjointBlocks, VolumeCounter};
+
+pub use aabox::Range;
 pub type Triple = (i32, i32, i32);
 
 #[derive(Debug, PartialEq)]
@@ -49,21 +55,8 @@ impl ReactorCore {
         let lower_bound = *range.start();
 
         self.blocks.retain(|block| {
-            if (block.range_x.end() < &lower_bound)
-                || (block.range_y.end() < &lower_bound)
-                || (block.range_z.end() < &lower_bound)
-            {
-                return false;
-            }
-
-            if (block.range_x.start() > &upper_bound)
-                || (block.range_y.start() > &upper_bound)
-                || (block.range_z.start() > &upper_bound)
-            {
-                return false;
-            }
-
-            true
+            let (min, max) = (block.cuboid.min(), block.cuboid.max());
+            max.iter().all(|x| *x >= lower_bound) && min.iter().all(|x| *x <= upper_bound)
         });
     }
 
@@ -112,12 +105,29 @@ impl ReactorCore {
         self.blocks = reduced_blocks;
     }
 
-    pub fn count_on_cubes(&self) -> usize {
+    /// exact amount of lit cubes inside the region, works before and after `combining`
+    pub fn count_on_in(&self, region: &Block) -> u128 {
+        let clipped: Vec<_> = self
+            .blocks
+            .iter()
+            .filter_map(|block| block.intersect(region, block.is_on))
+            .collect();
+
+        if self.is_combined {
+            let mut core = ReactorCore::new(clipped);
+            core.is_combined = true;
+            core.count_on_cubes()
+        } else {
+            DisjointBlocks::from_blocks(&clipped).count_on_cubes()
+        }
+    }
+
+    pub fn count_on_cubes(&self) -> u128 {
         if !self.is_combined {
             return 0;
         }
 
-        let mut count: i64 = 0;
+        let mut count: i128 = 0;
         for block in self.blocks.iter() {
             if block.is_on {
                 count += block.volume()
@@ -126,18 +136,13 @@ impl ReactorCore {
             }
         }
 
-        if count < 0 {
-            panic!("invalid volume")
-        }
-        count as usize
+        u128::try_from(count).expect("invalid volume")
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Block {
-    range_x: Range,
-    range_y: Range,
-    range_z: Range,
+    cuboid: AaBox<3>,
     is_on: bool,
 }
 
@@ -150,96 +155,72 @@ impl Block {
             (input.strip_prefix("off ").ok_or("invalid line")?, false)
         };
 
-        let (x_range_txt, y_range_txt, z_range_txt) = if let Some(Some((x, y, z))) = input
-            .split_once(",")
-            .map(|(x, rest)| rest.split_once(",").map(|(y, z)| (x, y, z)))
-        {
-            let x = x.trim_start_matches("x=");
-            let y = y.trim_start_matches("y=");
-            let z = z.trim_start_matches("z=");
-            (x, y, z)
-        } else {
-            return Err("invalid input".into());
-        };
-
-        let x_range = Self::range_txt_to_range(x_range_txt)?;
-        let y_range = Self::range_txt_to_range(y_range_txt)?;
-        let z_range = Self::range_txt_to_range(z_range_txt)?;
-        Ok(Block::new(x_range, y_range, z_range, is_on))
+        Ok(Block {
+            cuboid: AaBox::parse(input)?,
+            is_on,
+        })
     }
 
     pub fn new(range_x: Range, range_y: Range, range_z: Range, is_on: bool) -> Block {
         Block {
-            range_x,
-            range_y,
-            range_z,
+            cuboid: AaBox::new([range_x, range_y, range_z]),
             is_on,
         }
     }
 
-    pub fn on(&self, (x, y, z): &Triple) -> bool {
-        if self.range_x.contains(&x) && self.range_y.contains(&y) && self.range_z.contains(&z) {
-            self.is_on
-        } else {
-            false
-        }
+    pub fn cuboid(&self) -> &AaBox<3> {
+        &self.cuboid
+    }
+
+    pub fn on(&self, triple: &Triple) -> bool {
+        self.found(triple).unwrap_or(false)
     }
 
     pub fn found(&self, (x, y, z): &Triple) -> Option<bool> {
-        if self.range_x.contains(&x) && self.range_y.contains(&y) && self.range_z.contains(&z) {
+        if self.cuboid.contains(&[*x, *y, *z]) {
             Some(self.is_on)
         } else {
             None
         }
     }
 
-    fn range_txt_to_range(input: &str) -> Result<Range, Box<dyn std::error::Error>> {
-        if let Some(range) = input.split_once("..").map(
-            |(start, end)| -> Result<Range, Box<dyn std::error::Error>> {
-                let start: i32 = start.parse()?;
-                let end: i32 = end.parse()?;
-                Ok(start..=end)
-            },
-        ) {
-            range
-        } else {
-            Err("invalid range".into())
+    /// the eight regions split at zero, together they cover every coordinate
+    pub fn octants() -> Vec<Block> {
+        let halves = [i32::MIN..=-1, 0..=i32::MAX];
+        let mut octants = Vec::with_capacity(8);
+        for x in halves.iter() {
+            for y in halves.iter() {
+                for z in halves.iter() {
+                    octants.push(Block::new(x.clone(), y.clone(), z.clone(), true));
+                }
+            }
         }
+        octants
     }
 
-    pub fn volume(&self) -> i64 {
-        // why do I need to do +1 here ?!
-        (self.range_x.end() - self.range_x.start() + 1) as i64
-            * (self.range_y.end() - self.range_y.start() + 1) as i64
-            * (self.range_z.end() - self.range_z.start() + 1) as i64
+    pub fn volume(&self) -> i128 {
+        self.cuboid
+            .volume()
+            .expect("three i32 ranges always fit in i128")
     }
 
     pub fn intersect(&self, other: &Block, is_on: bool) -> Option<Block> {
-        let mut coordinates = [
-            (&self.range_x, &other.range_x),
-            (&self.range_y, &other.range_y),
-            (&self.range_z, &other.range_z),
-        ]
-        .into_iter()
-        .map(|(left, right)| {
-            let left_start = left.start();
-            let left_end = left.end();
-            let right_start = right.start();
-            let right_end = right.end();
-            let new_start = left_start.max(right_start);
-            let new_end = left_end.min(right_end);
-            if new_start <= new_end {
-                Some(*new_start..=*new_end)
-            } else {
-                None
-            }
+        Some(Block {
+            cuboid: self.cuboid.intersect(&other.cuboid)?,
+            is_on,
         })
-        .collect::<Option<Vec<_>>>()?;
+    }
 
-        let z = coordinates.remove(2);
-        let y = coordinates.remove(1);
-        let x = coordinates.remove(0);
-        Some(Block::new(x, y, z, is_on))
+    /// the parts of this block not covered by `other`, as at most 6 disjoint blocks
+    pub fn subtract(&self, other: &Block) -> Vec<Block> {
+        self.cuboid
+            .subtract(&other.cuboid)
+            .into_iter()
+            .map(|cuboid| Block {
+                cuboid,
+                is_on: self.is_on,
+            })
+            .collect()
     }
 }
 
@@ -260,7 +241,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn part_one(input_path: &str, combined: bool) -> Result<usize, Box<dyn std::error::Error>> {
+fn part_one(input_path: &str, combined: bool) -> Result<u128, Box<dyn std::error::Error>> {
     let data = read_to_string(input_path)?;
     let mut core = ReactorCore::from_text(&data)?;
 
@@ -268,40 +249,84 @@ fn part_one(input_path: &str, combined: bool) -> Result<usize, Box<dyn std::erro
         core.combining();
     }
 
-    // remove blocks we dont care about
-    core.reject_blocks_not_in_range(-50..=50);
-
-    // this can perfectly be done in parallel
-    let range3d = (-50..=50).into_par_iter().flat_map(|i| {
-        (-50..=50)
-            .into_par_iter()
-            .flat_map(move |j| (-50..=50).into_par_iter().map(move |k| (i, j, k)))
-    });
-    Ok(range3d.filter(|x| core.on(x)).count())
+    let region = Block::new(-50..=50, -50..=50, -50..=50, true);
+    Ok(core.count_on_in(&region))
 }
 
-fn part_two(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
+fn part_two(input_path: &str) -> Result<u128, Box<dyn std::error::Error>> {
     let data = read_to_string(input_path)?;
-    let mut core = ReactorCore::from_text(&data)?;
-    core.combining();
+    let core = ReactorCore::from_text(&data)?;
+    let disjoint = DisjointBlocks::from_blocks(&core.blocks);
 
-    let count = core.count_on_cubes();
-
-    Ok(count)
+    Ok(disjoint.count_on_cubes())
 }
 
+/// three overlapping cubes with a corner turned off
+#[cfg(test)]
+const EXAMPLE: &str = "
+on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10
+";
+
 #[test]
 fn day_22_part_one() {
     assert_eq!(658691, part_one(fetch_file_path(), false).unwrap())
 }
 
-// only fast enough to get if you run in release mode
-#[cfg(not(debug_assertions))]
 #[test]
 fn day_22_part_two() {
     assert_eq!(1228699515783640, part_two(fetch_file_path()).unwrap())
 }
 
+#[test]
+fn day_22_part_one_combined() {
+    assert_eq!(658691, part_one(fetch_file_path(), true).unwrap())
+}
+
+#[test]
+fn count_on_in_region() {
+    let core = ReactorCore::from_text(EXAMPLE).unwrap();
+
+    assert_eq!(
+        39,
+        core.count_on_in(&Block::new(0..=20, 0..=20, 0..=20, true))
+    );
+    assert_eq!(
+        2,
+        core.count_on_in(&Block::new(10..=10, 10..=10, 0..=20, true))
+    );
+    assert_eq!(
+        8,
+        core.count_on_in(&Block::new(12..=13, 12..=13, 12..=13, true))
+    );
+    assert_eq!(
+        0,
+        core.count_on_in(&Block::new(-5..=5, 0..=20, 0..=20, true))
+    );
+}
+
+#[test]
+fn count_on_per_octant() {
+    let data = read_to_string(fetch_file_path()).unwrap();
+    let core = ReactorCore::from_text(&data).unwrap();
+    let mut combined = ReactorCore::from_text(&data).unwrap();
+    combined.combining();
+
+    let octants: Vec<_> = Block::octants()
+        .iter()
+        .map(|octant| core.count_on_in(octant))
+        .collect();
+    let combined_octants: Vec<_> = Block::octants()
+        .iter()
+        .map(|octant| combined.count_on_in(octant))
+        .collect();
+
+    assert_eq!(octants, combined_octants);
+    assert_eq!(1228699515783640, octants.iter().sum::<u128>());
+}
+
 #[test]
 fn reactor_core_from_text() {
     let expected = ReactorCore::new(vec![
@@ -365,15 +390,28 @@ fn intersect_test() {
 
     let x = a.intersect(&b, false).unwrap();
 
+    assert_eq!(Block::new(11..=12, 11..=12, 11..=12, false), x)
+}
+
+#[test]
+fn subtract_test() {
+    let a = Block::new(10..=12, 10..=12, 10..=12, true);
+    let b = Block::new(11..=13, 11..=13, 11..=13, false);
+
+    let pieces = a.subtract(&b);
+    assert_eq!(3, pieces.len());
+    assert_eq!(27 - 8, pieces.iter().map(|x| x.volume()).sum::<i128>());
+    assert!(pieces
+        .iter()
+        .all(|x| x.is_on && x.intersect(&b, true).is_none()));
+
+    let inner = Block::new(11..=11, 11..=11, 11..=11, true);
+    assert_eq!(6, a.subtract(&inner).len());
+    assert_eq!(Vec::<Block>::new(), inner.subtract(&a));
     assert_eq!(
-        Block {
-            range_x: 11..=12,
-            range_y: 11..=12,
-            range_z: 11..=12,
-            is_on: false,
-        },
-        x
-    )
+        vec![b.clone()],
+        b.subtract(&Block::new(0..=1, 0..=1, 0..=1, true))
+    );
 }
 
 #[test]
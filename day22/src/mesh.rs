@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use aabox::AaBox;
+
+use crate::volume::{DisjointBlocks, VolumeCounter};
+use crate::{Block, ReactorCore};
+
+/// A face of a block, `cells` covers the other two axes in the order `axis + 1`, `axis + 2`.
+struct Face {
+    /// the face points in the positive direction of the axis
+    positive: bool,
+    cells: AaBox<2>,
+}
+
+/// Joins rectangles that share a whole edge until none do, the rectangles have to be disjoint.
+fn merge_rectangles(mut rectangles: Vec<AaBox<2>>) -> Vec<AaBox<2>> {
+    loop {
+        let before = rectangles.len();
+        for axis in 0..2 {
+            // sorted by the other axis first, neighbours along `axis` end up next to each other
+            let other = 1 - axis;
+            rectangles.sort_by_key(|x| (x.min()[other], x.max()[other], x.min()[axis]));
+            let mut merged: Vec<AaBox<2>> = Vec::with_capacity(rectangles.len());
+            for rectangle in rectangles {
+                match merged.last_mut() {
+                    Some(last)
+                        if last.range(other) == rectangle.range(other)
+                            && last.max()[axis] + 1 == rectangle.min()[axis] =>
+                    {
+                        let mut ranges = last.ranges().clone();
+                        ranges[axis] = last.min()[axis]..=rectangle.max()[axis];
+                        *last = AaBox::new(ranges);
+                    }
+                    _ => merged.push(rectangle),
+                }
+            }
+            rectangles = merged;
+        }
+        if rectangles.len() == before {
+            return rectangles;
+        }
+    }
+}
+
+/// Quads around the lit region, parts of faces where two blocks touch are left out and
+/// coplanar faces sharing an edge are joined.
+#[derive(Debug, Default, PartialEq)]
+pub struct Mesh {
+    vertices: Vec<[i64; 3]>,
+    /// counter clockwise when looking at the outside
+    faces: Vec<[usize; 4]>,
+}
+
+impl Mesh {
+    /// the blocks have to be disjoint, a cube at `x` spans from `x` to `x + 1`
+    pub fn from_blocks(blocks: &[Block]) -> Mesh {
+        // faces grouped by the plane they are on
+        let mut planes: HashMap<(usize, i64), Vec<Face>> = HashMap::new();
+        for block in blocks {
+            let (min, max) = (block.cuboid.min(), block.cuboid.max());
+            for axis in 0..3 {
+                let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                let cells = AaBox::new([min[u]..=max[u], min[v]..=max[v]]);
+                for (positive, plane) in [(false, min[axis] as i64), (true, max[axis] as i64 + 1)] {
+                    planes.entry((axis, plane)).or_default().push(Face {
+                        positive,
+                        cells: cells.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut mesh = Mesh::default();
+        let mut positions = HashMap::new();
+        for ((axis, plane), faces) in planes.iter() {
+            for positive in [false, true] {
+                let mut visible = Vec::new();
+                for face in faces.iter().filter(|x| x.positive == positive) {
+                    // only faces pointing the other way can cover it, the blocks are disjoint
+                    let mut cells = vec![face.cells.clone()];
+                    for other in faces.iter().filter(|x| x.positive != positive) {
+                        cells = cells
+                            .iter()
+                            .flat_map(|x| x.subtract(&other.cells))
+                            .collect();
+                    }
+                    visible.extend(cells);
+                }
+                for cells in merge_rectangles(visible) {
+                    mesh.push_quad(&mut positions, *axis, *plane, positive, &cells);
+                }
+            }
+        }
+        mesh
+    }
+
+    fn push_quad(
+        &mut self,
+        positions: &mut HashMap<[i64; 3], usize>,
+        axis: usize,
+        plane: i64,
+        positive: bool,
+        cells: &AaBox<2>,
+    ) {
+        let (min, max) = (cells.min(), cells.max());
+        let (u0, v0) = (min[0] as i64, min[1] as i64);
+        let (u1, v1) = (max[0] as i64 + 1, max[1] as i64 + 1);
+        let mut corners = [(u0, v0), (u1, v0), (u1, v1), (u0, v1)];
+        if !positive {
+            corners.reverse();
+        }
+
+        let mut quad = [0; 4];
+        for (index, (u, v)) in corners.into_iter().enumerate() {
+            let mut vertex = [0; 3];
+            vertex[axis] = plane;
+            vertex[(axis + 1) % 3] = u;
+            vertex[(axis + 2) % 3] = v;
+            quad[index] = *positions.entry(vertex).or_insert_with(|| {
+                self.vertices.push(vertex);
+                self.vertices.len() - 1
+            });
+        }
+        self.faces.push(quad);
+    }
+
+    pub fn vertices(&self) -> &[[i64; 3]] {
+        &self.vertices
+    }
+
+    pub fn faces(&self) -> &[[usize; 4]] {
+        &self.faces
+    }
+
+    /// Wavefront OBJ, indices start at one
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "o reactor")?;
+        for [x, y, z] in self.vertices.iter() {
+            writeln!(writer, "v {} {} {}", x, y, z)?;
+        }
+        for [a, b, c, d] in self.faces.iter() {
+            writeln!(writer, "f {} {} {} {}", a + 1, b + 1, c + 1, d + 1)?;
+        }
+        Ok(())
+    }
+
+    /// ASCII PLY, indices start at zero. The coordinates go up to 2^31, one past `int`, so they
+    /// are declared as `double` which holds them exactly.
+    pub fn write_ply<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format ascii 1.0")?;
+        writeln!(writer, "element vertex {}", self.vertices.len())?;
+        for axis in ["x", "y", "z"] {
+            writeln!(writer, "property double {}", axis)?;
+        }
+        writeln!(writer, "element face {}", self.faces.len())?;
+        writeln!(writer, "property list uchar int vertex_indices")?;
+        writeln!(writer, "end_header")?;
+        for [x, y, z] in self.vertices.iter() {
+            writeln!(writer, "{} {} {}", x, y, z)?;
+        }
+        for [a, b, c, d] in self.faces.iter() {
+            writeln!(writer, "4 {} {} {} {}", a, b, c, d)?;
+        }
+        Ok(())
+    }
+}
+
+impl ReactorCore {
+    /// mesh of the lit region, built from the steps so it has to be called before `combining`
+    pub fn mesh(&self) -> Result<Mesh, Box<dyn std::error::Error>> {
+        if self.is_combined {
+            return Err("a combined core has no steps left to build a mesh from".into());
+        }
+        let disjoint = DisjointBlocks::from_blocks(&self.blocks);
+        Ok(Mesh::from_blocks(disjoint.blocks()))
+    }
+}
+
+#[cfg(test)]
+mod mesh_tests {
+    use super::{merge_rectangles, Mesh};
+    use crate::{fetch_file_path, Block, ReactorCore, EXAMPLE};
+    use aabox::AaBox;
+    use std::fs::read_to_string;
+
+    fn area(mesh: &Mesh) -> i64 {
+        mesh.faces()
+            .iter()
+            .map(|face| {
+                let [a, _, c, _] = face.map(|x| mesh.vertices()[x]);
+                (0..3)
+                    .map(|axis| (a[axis] - c[axis]).abs())
+                    .filter(|x| *x != 0)
+                    .product::<i64>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn single_block() {
+        let mesh = Mesh::from_blocks(&[Block::new(0..=1, 0..=2, 0..=3, true)]);
+
+        assert_eq!(8, mesh.vertices().len());
+        assert_eq!(6, mesh.faces().len());
+        assert_eq!(2 * (2 * 3 + 2 * 4 + 3 * 4), area(&mesh));
+    }
+
+    #[test]
+    fn touching_blocks() {
+        let mesh = Mesh::from_blocks(&[
+            Block::new(10..=11, 0..=0, 0..=0, true),
+            Block::new(12..=13, 0..=0, 0..=0, true),
+        ]);
+        assert_eq!(8, mesh.vertices().len());
+        assert_eq!(6, mesh.faces().len());
+        assert_eq!(2 * (4 + 4 + 1), area(&mesh));
+
+        // only part of the side is covered
+        let mesh = Mesh::from_blocks(&[
+            Block::new(0..=1, 0..=1, 0..=1, true),
+            Block::new(2..=2, 0..=0, 0..=0, true),
+        ]);
+        assert_eq!(12, mesh.faces().len());
+        assert_eq!(6 * 4 + 4, area(&mesh));
+    }
+
+    #[test]
+    fn merged_rectangles() {
+        // a 2x2 square out of four cells and an L shape that stays two rectangles
+        let cells = |u: i32, v: i32| AaBox::new([u..=u, v..=v]);
+        let square = merge_rectangles(vec![cells(0, 0), cells(1, 1), cells(0, 1), cells(1, 0)]);
+        assert_eq!(vec![AaBox::new([0..=1, 0..=1])], square);
+
+        let l_shape = merge_rectangles(vec![cells(0, 0), cells(1, 0), cells(0, 1)]);
+        assert_eq!(2, l_shape.len());
+        assert_eq!(3, l_shape.iter().map(|x| x.volume().unwrap()).sum::<i128>());
+    }
+
+    #[test]
+    fn faces_point_outwards() {
+        let mesh = Mesh::from_blocks(&[Block::new(0..=0, 0..=0, 0..=0, true)]);
+
+        for face in mesh.faces() {
+            let [a, b, c, _] = face.map(|x| mesh.vertices()[x]);
+            let (u, v) = (
+                [b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+                [c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+            );
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            // the cube is centered on 0.5, the normal has to point away from it
+            let center: i64 = (0..3).map(|x| normal[x] * (2 * a[x] - 1)).sum();
+            assert!(center > 0, "{:?}", face);
+        }
+    }
+
+    #[test]
+    fn example_surface() {
+        let core = ReactorCore::from_text(EXAMPLE).unwrap();
+        let mesh = core.mesh().unwrap();
+
+        // every side of a lit cube next to an unlit cube is part of the surface
+        let mut expected = 0;
+        for x in 8..=14 {
+            for y in 8..=14 {
+                for z in 8..=14 {
+                    if !core.on(&(x, y, z)) {
+                        continue;
+                    }
+                    for (dx, dy, dz) in [
+                        (1, 0, 0),
+                        (-1, 0, 0),
+                        (0, 1, 0),
+                        (0, -1, 0),
+                        (0, 0, 1),
+                        (0, 0, -1),
+                    ] {
+                        if !core.on(&(x + dx, y + dy, z + dz)) {
+                            expected += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(expected, area(&mesh));
+
+        let mut combined = ReactorCore::from_text(EXAMPLE).unwrap();
+        combined.combining();
+        assert!(combined.mesh().is_err());
+    }
+
+    #[test]
+    fn obj_and_ply() {
+        let mesh = Mesh::from_blocks(&[Block::new(0..=0, 0..=0, 0..=0, true)]);
+
+        let mut obj = Vec::new();
+        mesh.write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        assert!(obj.starts_with("o reactor\nv "));
+        assert_eq!(8, obj.lines().filter(|x| x.starts_with("v ")).count());
+        assert_eq!(6, obj.lines().filter(|x| x.starts_with("f ")).count());
+        assert!(obj.lines().any(|x| x.split(' ').any(|y| y == "8")));
+
+        let mut ply = Vec::new();
+        mesh.write_ply(&mut ply).unwrap();
+        let ply = String::from_utf8(ply).unwrap();
+        assert!(ply.starts_with("ply\nformat ascii 1.0\nelement vertex 8\n"));
+        assert!(ply.contains("element face 6\n"));
+        assert_eq!(6, ply.lines().filter(|x| x.starts_with("4 ")).count());
+
+        // the far corner of the largest cube is one past `i32::MAX`
+        let mesh = Mesh::from_blocks(&[Block::new(
+            i32::MAX..=i32::MAX,
+            i32::MIN..=i32::MIN,
+            0..=0,
+            true,
+        )]);
+        let mut ply = Vec::new();
+        mesh.write_ply(&mut ply).unwrap();
+        let ply = String::from_utf8(ply).unwrap();
+        assert!(
+            ply.contains(
+                "property double x
+"
+            ) && !ply.contains(
+                "property int x
+"
+            )
+        );
+        assert!(ply.contains("\n2147483648 -2147483648 0\n"));
+    }
+
+    #[test]
+    fn full_input() {
+        let core = ReactorCore::from_text(&read_to_string(fetch_file_path()).unwrap()).unwrap();
+        let mesh = core.mesh().unwrap();
+
+        let mut ply = Vec::new();
+        mesh.write_ply(&mut ply).unwrap();
+        assert!(!mesh.faces().is_empty());
+        // a few megabytes at most, a voxel per cube would never fit
+        assert!(ply.len() < 50_000_000);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::volume::{DisjointBlocks, VolumeCounter};
+use crate::Block;
+
+/// What a single step changed in the lit blocks, enough to undo it again.
+#[derive(Debug, PartialEq, Clone)]
+struct Change {
+    block: Block,
+    /// blocks the step cut into, with their position before the step
+    removed: Vec<(usize, Block)>,
+    /// amount of blocks the step appended at the end
+    added: usize,
+}
+
+/// Cubes that changed between two steps.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Diff {
+    pub turned_on: u128,
+    pub turned_off: u128,
+}
+
+/// Applies reboot steps one at a time, keeping the lit region as disjoint blocks and an
+/// undo log so every earlier step can be looked at or returned to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Reactor {
+    lit: Vec<Block>,
+    changes: Vec<Change>,
+    /// lit cubes after each step, the first entry is before any step
+    counts: Vec<u128>,
+}
+
+impl Default for Reactor {
+    fn default() -> Reactor {
+        Reactor {
+            lit: Vec::new(),
+            changes: Vec::new(),
+            counts: vec![0],
+        }
+    }
+}
+
+impl Reactor {
+    pub fn new() -> Reactor {
+        Reactor::default()
+    }
+
+    /// applies the next step and returns the amount of lit cubes after it
+    pub fn apply(&mut self, block: Block) -> u128 {
+        let mut kept = Vec::with_capacity(self.lit.len());
+        let mut removed = Vec::new();
+        for (position, current) in self.lit.drain(..).enumerate() {
+            if current.intersect(&block, current.is_on).is_some() {
+                removed.push((position, current));
+            } else {
+                kept.push(current);
+            }
+        }
+
+        let mut added: Vec<_> = removed
+            .iter()
+            .flat_map(|(_, current)| current.subtract(&block))
+            .collect();
+        if block.is_on {
+            added.push(block.clone());
+        }
+
+        let added_volume: u128 = added.iter().map(|x| x.volume() as u128).sum();
+        let removed_volume: u128 = removed.iter().map(|(_, x)| x.volume() as u128).sum();
+        let count = self.count_on_cubes() + added_volume - removed_volume;
+
+        self.changes.push(Change {
+            block,
+            removed,
+            added: added.len(),
+        });
+        kept.extend(added);
+        self.lit = kept;
+        self.counts.push(count);
+        count
+    }
+
+    /// amount of steps applied so far
+    pub fn steps(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// the applied steps in order
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.changes.iter().map(|x| &x.block)
+    }
+
+    /// lit cubes after each step, the first entry is before any step
+    pub fn counts(&self) -> &[u128] {
+        &self.counts
+    }
+
+    pub fn count_on_cubes(&self) -> u128 {
+        *self
+            .counts
+            .last()
+            .expect("there is always a count before any step")
+    }
+
+    /// disjoint blocks that are lit after the given step
+    pub fn lit_at(&self, step: usize) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
+        self.check_step(step)?;
+        let mut lit = self.lit.clone();
+        for change in self.changes[step..].iter().rev() {
+            Self::undo(&mut lit, change);
+        }
+        Ok(lit)
+    }
+
+    /// cubes that are lit after step `to` but not after `from`, and the other way around
+    pub fn diff(&self, from: usize, to: usize) -> Result<Diff, Box<dyn std::error::Error>> {
+        let before = self.lit_at(from)?;
+        let after = self.lit_at(to)?;
+        let only_in = |blocks: &[Block], others: &[Block]| {
+            let mut steps = blocks.to_vec();
+            steps.extend(others.iter().map(|x| Block {
+                is_on: false,
+                ..x.clone()
+            }));
+            DisjointBlocks::from_blocks(&steps).count_on_cubes()
+        };
+
+        Ok(Diff {
+            turned_on: only_in(&after, &before),
+            turned_off: only_in(&before, &after),
+        })
+    }
+
+    /// undoes every step after the given one
+    pub fn rollback(&mut self, step: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_step(step)?;
+        for change in self.changes.drain(step..).rev() {
+            Self::undo(&mut self.lit, &change);
+        }
+        self.counts.truncate(step + 1);
+        Ok(())
+    }
+
+    fn undo(lit: &mut Vec<Block>, change: &Change) {
+        lit.truncate(lit.len() - change.added);
+        for (position, block) in change.removed.iter() {
+            lit.insert(*position, block.clone());
+        }
+    }
+
+    fn check_step(&self, step: usize) -> Result<(), Box<dyn std::error::Error>> {
+        if step > self.steps() {
+            Err(format!(
+                "step {} was not applied, there are {} steps",
+                step,
+                self.steps()
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl VolumeCounter for Reactor {
+    fn from_blocks(blocks: &[Block]) -> Reactor {
+        let mut reactor = Reactor::new();
+        for block in blocks {
+            reactor.apply(block.clone());
+        }
+        reactor
+    }
+
+    fn count_on_cubes(&self) -> u128 {
+        Reactor::count_on_cubes(self)
+    }
+}
+
+#[cfg(test)]
+mod timeline_tests {
+    use super::{Diff, Reactor};
+    use crate::volume::VolumeCounter;
+    use crate::{fetch_file_path, ReactorCore, EXAMPLE};
+    use std::fs::read_to_string;
+
+    fn reactor(input: &str) -> Reactor {
+        Reactor::from_blocks(&ReactorCore::from_text(input).unwrap().blocks)
+    }
+
+    #[test]
+    fn counts_per_step() {
+        let reactor = reactor(EXAMPLE);
+
+        assert_eq!(4, reactor.steps());
+        assert_eq!(&[0, 27, 46, 38, 39], reactor.counts());
+    }
+
+    #[test]
+    fn diff_between_steps() {
+        let reactor = reactor(EXAMPLE);
+
+        assert_eq!(
+            Diff {
+                turned_on: 19,
+                turned_off: 8
+            },
+            reactor.diff(1, 3).unwrap()
+        );
+        assert_eq!(
+            Diff {
+                turned_on: 8,
+                turned_off: 19
+            },
+            reactor.diff(3, 1).unwrap()
+        );
+        assert_eq!(Diff::default(), reactor.diff(2, 2).unwrap());
+        assert!(reactor.diff(0, 5).is_err());
+    }
+
+    #[test]
+    fn rollback() {
+        let mut reactor = reactor(EXAMPLE);
+        let blocks: Vec<_> = reactor.blocks().cloned().collect();
+
+        reactor.rollback(2).unwrap();
+        assert_eq!(46, reactor.count_on_cubes());
+        assert_eq!(Reactor::from_blocks(&blocks[..2]), reactor);
+
+        assert_eq!(38, reactor.apply(blocks[2].clone()));
+        reactor.rollback(0).unwrap();
+        assert_eq!(Reactor::new(), reactor);
+        assert!(reactor.rollback(1).is_err());
+    }
+
+    #[test]
+    fn full_input() {
+        let mut reactor = reactor(&read_to_string(fetch_file_path()).unwrap());
+
+        assert_eq!(1228699515783640, reactor.count_on_cubes());
+        // the first 20 steps are the initialization procedure
+        let lit = reactor.lit_at(20).unwrap();
+        reactor.rollback(20).unwrap();
+        assert_eq!(658691, reactor.count_on_cubes());
+        assert_eq!(lit, reactor.lit_at(20).unwrap());
+    }
+}
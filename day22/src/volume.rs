@@ -0,0 +1,205 @@
+use crate::{Block, ReactorCore};
+
+/// Counts the cubes that are on after applying all blocks in order.
+pub trait VolumeCounter {
+    fn from_blocks(blocks: &[Block]) -> Self
+    where
+        Self: Sized;
+
+    fn count_on_cubes(&self) -> u128;
+}
+
+impl VolumeCounter for ReactorCore {
+    fn from_blocks(blocks: &[Block]) -> ReactorCore {
+        let mut core = ReactorCore::new(blocks.to_vec());
+        core.combining();
+        core
+    }
+
+    fn count_on_cubes(&self) -> u128 {
+        ReactorCore::count_on_cubes(self)
+    }
+}
+
+/// Keeps the lit region as a set of disjoint blocks that are all on.
+#[derive(Debug, Default, PartialEq)]
+pub struct DisjointBlocks {
+    blocks: Vec<Block>,
+}
+
+impl DisjointBlocks {
+    pub fn apply(&mut self, block: &Block) {
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for current in self.blocks.iter() {
+            blocks.extend(current.subtract(block));
+        }
+        if block.is_on {
+            blocks.push(block.clone());
+        }
+        self.blocks = blocks;
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+}
+
+impl VolumeCounter for DisjointBlocks {
+    fn from_blocks(blocks: &[Block]) -> DisjointBlocks {
+        let mut disjoint = DisjointBlocks::default();
+        for block in blocks {
+            disjoint.apply(block);
+        }
+        disjoint
+    }
+
+    fn count_on_cubes(&self) -> u128 {
+        self.blocks.iter().map(|x| x.volume() as u128).sum()
+    }
+}
+
+/// Sweeps over the compressed x and y coordinates, in every cell of that grid the last
+/// block covering a z interval decides if it is on.
+#[derive(Debug, Default, PartialEq)]
+pub struct CompressedGrid {
+    blocks: Vec<Block>,
+}
+
+impl CompressedGrid {
+    /// sorted boundaries, every interval between two of them is either fully covered or not
+    fn boundaries<'a, F>(blocks: &[&'a Block], axis: F) -> Vec<i64>
+    where
+        F: Fn(&'a Block) -> &'a crate::Range,
+    {
+        let mut boundaries: Vec<_> = blocks
+            .iter()
+            .flat_map(|block| {
+                let range = axis(block);
+                [*range.start() as i64, *range.end() as i64 + 1]
+            })
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        boundaries
+    }
+
+    fn covering<'a, F>(blocks: &[&'a Block], at: i64, axis: F) -> Vec<&'a Block>
+    where
+        F: Fn(&'a Block) -> &'a crate::Range,
+    {
+        blocks
+            .iter()
+            .copied()
+            .filter(|block| {
+                let range = axis(block);
+                *range.start() as i64 <= at && at <= *range.end() as i64
+            })
+            .collect()
+    }
+}
+
+impl VolumeCounter for CompressedGrid {
+    fn from_blocks(blocks: &[Block]) -> CompressedGrid {
+        CompressedGrid {
+            blocks: blocks.to_vec(),
+        }
+    }
+
+    fn count_on_cubes(&self) -> u128 {
+        let blocks: Vec<_> = self.blocks.iter().collect();
+        let mut count: u128 = 0;
+
+        let xs = Self::boundaries(&blocks, |x| x.cuboid.range(0));
+        for x in xs.windows(2) {
+            let in_x = Self::covering(&blocks, x[0], |x| x.cuboid.range(0));
+            let ys = Self::boundaries(&in_x, |x| x.cuboid.range(1));
+            for y in ys.windows(2) {
+                let in_y = Self::covering(&in_x, y[0], |x| x.cuboid.range(1));
+                let zs = Self::boundaries(&in_y, |x| x.cuboid.range(2));
+                for z in zs.windows(2) {
+                    let last = Self::covering(&in_y, z[0], |x| x.cuboid.range(2)).pop();
+                    if last.is_some_and(|x| x.is_on) {
+                        count += [x, y, z]
+                            .iter()
+                            .map(|x| (x[1] - x[0]) as u128)
+                            .product::<u128>();
+                    }
+                }
+            }
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod volume_tests {
+    use super::{CompressedGrid, DisjointBlocks, VolumeCounter};
+    use crate::timeline::Reactor;
+    use crate::{fetch_file_path, ReactorCore, EXAMPLE};
+    use std::fs::read_to_string;
+    use std::time::Instant;
+
+    fn count<V: VolumeCounter>(input: &str) -> u128 {
+        let core = ReactorCore::from_text(input).unwrap();
+        V::from_blocks(&core.blocks).count_on_cubes()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(39, count::<ReactorCore>(EXAMPLE));
+        assert_eq!(39, count::<DisjointBlocks>(EXAMPLE));
+        assert_eq!(39, count::<CompressedGrid>(EXAMPLE));
+    }
+
+    #[test]
+    fn full_input() {
+        let data = read_to_string(fetch_file_path()).unwrap();
+
+        assert_eq!(1228699515783640, count::<DisjointBlocks>(&data));
+        assert_eq!(1228699515783640, count::<CompressedGrid>(&data));
+    }
+
+    #[test]
+    fn huge_blocks() {
+        let input =
+            "on x=-2000000000..2000000000,y=-2000000000..2000000000,z=-2000000000..2000000000";
+
+        assert_eq!(64000000048000000012000000001, count::<ReactorCore>(input));
+        assert_eq!(
+            64000000048000000012000000001,
+            count::<DisjointBlocks>(input)
+        );
+        assert_eq!(
+            64000000048000000012000000001,
+            count::<CompressedGrid>(input)
+        );
+        assert_eq!(64000000048000000012000000001, count::<Reactor>(input));
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_volume_counters() {
+        let data = read_to_string(fetch_file_path()).unwrap();
+
+        let counters = [
+            (
+                "inclusion exclusion",
+                count::<ReactorCore> as fn(&str) -> u128,
+            ),
+            ("disjoint blocks", count::<DisjointBlocks>),
+            ("compressed grid", count::<CompressedGrid>),
+        ];
+        let counts: Vec<_> = counters
+            .iter()
+            .map(|(label, counter)| {
+                let start = Instant::now();
+                let count = counter(&data);
+                println!("{}: {:?}", label, start.elapsed());
+                count
+            })
+            .collect();
+
+        assert!(counts.iter().all(|x| *x == counts[0]));
+    }
+}
@@ -1,9 +1,29 @@
-use maplit::{btreemap, btreeset};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs::read_to_string;
 use std::path::Path;
 
-#[derive(Debug, Eq, PartialEq, strum::Display, strum::EnumString, strum::IntoStaticStr)]
+pub mod render;
+pub mod state;
+
+use state::Topology;
+
+pub type Position = (usize, usize);
+pub type Spots = BTreeMap<Position, Option<Amphipod>>;
+
+#[derive(
+    Debug,
+    Eq,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+    strum::Display,
+    strum::EnumString,
+    strum::IntoStaticStr,
+    strum::EnumIter,
+)]
 pub enum Amphipod {
     #[strum(to_string = "A")]
     Amber = 1,
@@ -15,7 +35,14 @@ pub enum Amphipod {
     Desert = 1000,
 }
 
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord)]
+impl Amphipod {
+    /// energy needed for a single step
+    pub fn energy(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub enum Direction {
     Left,
     Right,
@@ -23,15 +50,46 @@ pub enum Direction {
     Down,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+impl Direction {
+    /// the spot next to a position, rows go down and columns go right
+    pub fn step(&self, (row, column): &Position) -> Option<Position> {
+        match self {
+            Direction::Left => Some((*row, column.checked_sub(1)?)),
+            Direction::Right => Some((*row, column + 1)),
+            Direction::Up => Some((row.checked_sub(1)?, *column)),
+            Direction::Down => Some((row + 1, *column)),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum Interior {
     Room(Amphipod),
     Hall,
 }
 
-#[derive(Debug, PartialEq)]
+/// A single amphipod moving, with the energy it takes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Move {
+    pub amphipod: Amphipod,
+    pub from: Position,
+    pub to: Position,
+    pub energy: usize,
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} moves from {:?} to {:?} using {} energy",
+            self.amphipod, self.from, self.to, self.energy
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Board {
-    pub spots: BTreeMap<(usize, usize), Option<Amphipod>>,
+    pub spots: Spots,
     pub connections: BTreeMap<(usize, usize), BTreeSet<Direction>>,
     pub interior: BTreeMap<(usize, usize), Interior>,
 }
@@ -41,74 +99,127 @@ impl Default for Board {
     ///   x x x x
     ///   x x x x
     fn default() -> Board {
-        use Direction::*;
-        use Interior::*;
-
-        Board {
-            spots: btreemap! {
-                (0,0) => None,
-                (0,1) => None,
-                (0,2) => None,
-                (0,3) => None,
-                (0,4) => None,
-                (0,5) => None,
-                (0,6) => None,
-                (0,7) => None,
-                (0,8) => None,
-                (0,9) => None,
-                (0,10) => None,
-                (1,2) => None,
-                (2,2) => None,
-                (1,4) => None,
-                (2,4) => None,
-                (1,6) => None,
-                (2,6) => None,
-                (1,8) => None,
-                (2,8) => None,
-            },
-            connections: btreemap! {
-                (0,0) => btreeset!{Right},
-                (0,1) => btreeset!{Left, Right},
-                (0,2) => btreeset!{Left, Right, Down},
-                (0,3) => btreeset!{Left, Right},
-                (0,4) => btreeset!{Left, Right, Down},
-                (0,5) => btreeset!{Left, Right},
-                (0,6) => btreeset!{Left, Right, Down},
-                (0,7) => btreeset!{Left, Right},
-                (0,8) => btreeset!{Left, Right, Down},
-                (0,9) => btreeset!{Left, Right},
-                (0,10) => btreeset!{Left},
-                (1,2) => btreeset!{Down, Up},
-                (2,2) =>  btreeset!{Up},
-                (1,4) => btreeset!{Down, Up},
-                (2,4) => btreeset!{Up},
-                (1,6) => btreeset!{Down, Up},
-                (2,6) => btreeset!{Up},
-                (1,8) => btreeset!{Down, Up},
-                (2,8) => btreeset!{Up},
-            },
-            interior: btreemap! {
-                (0,0) => Hall,
-                (0,1) => Hall,
-                (0,2) => Hall,
-                (0,3) => Hall,
-                (0,4) => Hall,
-                (0,5) => Hall,
-                (0,6) => Hall,
-                (0,7) => Hall,
-                (0,8) => Hall,
-                (0,9) => Hall,
-                (0,10) => Hall,
-                (1,2) => Room(Amphipod::Amber),
-                (2,2) => Room(Amphipod::Amber),
-                (1,4) => Room(Amphipod::Bronze),
-                (2,4) => Room(Amphipod::Bronze),
-                (1,6) => Room(Amphipod::Copper),
-                (2,6) => Room(Amphipod::Copper),
-                (1,8) => Room(Amphipod::Desert),
-                (2,8) => Room(Amphipod::Desert),
-            },
+        Board::from_text(
+            "#############
+#...........#
+###.#.#.#.###
+  #.#.#.#.#
+  #########",
+        )
+        .expect("the default diagram is valid")
+    }
+}
+
+impl Board {
+    /// Reads the board from the diagram, the first row of spots is the hallway and every
+    /// column of spots below it is a room, given to the amphipods from left to right.
+    pub fn from_text(input: &str) -> Result<Board, Box<dyn std::error::Error>> {
+        use strum::IntoEnumIterator;
+
+        let mut spots = BTreeMap::new();
+        for (row, line) in input.lines().skip(1).enumerate() {
+            for (column, ch) in line.chars().enumerate().skip(1) {
+                let occupant = match ch {
+                    '#' | ' ' => continue,
+                    '.' => None,
+                    _ => Some(ch.to_string().parse()?),
+                };
+                spots.insert((row, column - 1), occupant);
+            }
+        }
+        if !spots.keys().any(|(row, _)| *row == 0) {
+            return Err("the diagram has no hallway".into());
         }
+
+        let connections = spots
+            .keys()
+            .map(|position| {
+                let directions = [
+                    Direction::Left,
+                    Direction::Right,
+                    Direction::Up,
+                    Direction::Down,
+                ]
+                .into_iter()
+                // rooms are shafts, only the hallway connects sideways
+                .filter(|x| position.0 == 0 || matches!(x, Direction::Up | Direction::Down))
+                .filter(|x| x.step(position).is_some_and(|x| spots.contains_key(&x)))
+                .collect();
+                (*position, directions)
+            })
+            .collect();
+
+        let mut columns: Vec<_> = spots
+            .keys()
+            .filter(|(row, _)| *row > 0)
+            .map(|(_, column)| *column)
+            .collect();
+        columns.sort_unstable();
+        columns.dedup();
+        if columns.len() > Amphipod::iter().count() {
+            return Err("there are more rooms than kinds of amphipods".into());
+        }
+        let rooms: BTreeMap<_, _> = columns.into_iter().zip(Amphipod::iter()).collect();
+        let interior = spots
+            .keys()
+            .map(|(row, column)| match row {
+                0 => ((*row, *column), Interior::Hall),
+                _ => ((*row, *column), Interior::Room(rooms[column])),
+            })
+            .collect();
+
+        Ok(Board {
+            spots,
+            connections,
+            interior,
+        })
+    }
+
+    /// hallway spots right above a room, nobody stops there
+    fn is_entrance(&self, position: &Position) -> bool {
+        self.interior[position] == Interior::Hall
+            && self.connections[position].contains(&Direction::Down)
+    }
+
+    /// all legal moves as `from`, `to` and the energy they take, the rules live in `Topology`
+    pub fn moves(&self) -> Vec<(Position, Position, usize)> {
+        let topology = Topology::new(self);
+        topology
+            .moves(&topology.unpacked(self))
+            .into_iter()
+            .map(|(from, to, energy)| (topology.position(from), topology.position(to), energy))
+            .collect()
+    }
+
+    pub fn is_done(&self) -> bool {
+        let topology = Topology::new(self);
+        topology.is_done(&topology.unpacked(self))
+    }
+
+    /// least energy to organize the amphipods, boards too large for a packed state are solved
+    /// unpacked
+    pub fn solve(&self) -> Option<usize> {
+        let topology = Topology::new(self);
+        match topology.state(self) {
+            Ok(state) => topology.solve(state),
+            Err(_) => topology.solve(topology.unpacked(self)),
+        }
+    }
+
+    /// the cheapest moves that organize the amphipods, unpacked for large boards like `solve`
+    pub fn solution(&self) -> Result<Vec<Move>, Box<dyn std::error::Error>> {
+        let topology = Topology::new(self);
+        let moves = match topology.state(self) {
+            Ok(state) => topology.solution(state),
+            Err(_) => topology.solution(topology.unpacked(self)),
+        };
+        Ok(moves.ok_or("no solution")?)
+    }
+
+    /// moves the amphipod, the move is not checked
+    pub fn apply(&mut self, step: &Move) {
+        let amphipod = self.spots.insert(step.from, None).flatten();
+        self.spots.insert(step.to, amphipod);
     }
 }
 
@@ -122,49 +233,141 @@ fn fetch_file_path() -> &'static str {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input_file = fetch_file_path();
     println!("part one: {:?}", part_one(input_file)?);
-    // println!("part two: {:?}", part_two(input_file)?);
+    println!("part two: {:?}", part_two(input_file)?);
 
     Ok(())
 }
 
 fn part_one(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
     let data = read_to_string(input_path)?;
-    let mut board = Board::default();
-
-    for (i, line) in data.lines().skip(2).take(2).enumerate() {
-        for (j, x) in line
-            .chars()
-            .filter(|ch| ch != &'#' && ch != &' ')
-            .map(|ch| ch.to_string())
-            .map(|s| s.parse())
-            .enumerate()
-        {
-            let key = board
-                .spots
-                .get_mut(&(i + 1, 2 * j + 2))
-                .expect("the board is invalid");
-            *key = Some(x?);
-        }
-    }
+    let board = Board::from_text(&data)?;
+
+    Ok(board.solve().ok_or("no solution")?)
+}
+
+/// inserts the two folded lines of part two between the rooms
+fn unfold(input: &str) -> String {
+    let mut lines: Vec<_> = input.lines().collect();
+    lines.insert(3, "  #D#C#B#A#");
+    lines.insert(4, "  #D#B#A#C#");
+    lines.join("\n")
+}
 
-    dbg!(board);
+fn part_two(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let data = read_to_string(input_path)?;
+    let board = Board::from_text(&unfold(&data))?;
 
-    Ok(0)
+    Ok(board.solve().ok_or("no solution")?)
 }
 
-// fn part_two(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
-//     let data = read_to_string(input_path)?;
+#[cfg(test)]
+const EXAMPLE: &str = "#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
 
-//     Ok(0)
-// }
+#[test]
+fn moves_from_start() {
+    let board = Board::from_text(EXAMPLE).unwrap();
+    let moves = board.moves();
 
-// #[test]
-// fn day_23_part_one() {
-//     assert_eq!(658691, part_one(fetch_file_path(), false).unwrap())
-// }
+    // only the top of each room can leave, to one of the seven hallway spots
+    assert_eq!(4 * 7, moves.len());
+    assert!(moves.contains(&((1, 4), (0, 3), 2 * 100)));
+    assert!(!moves.iter().any(|x| x.0 .0 == 2 || board.is_entrance(&x.1)));
+}
+
+#[test]
+fn settled_amphipods() {
+    let board = Board::from_text(EXAMPLE).unwrap();
+    let topology = Topology::new(&board);
+    let state = topology.unpacked(&board);
+    let is_settled = |position| topology.is_settled(&state, topology.index(&position).unwrap());
+
+    // the bottom amber and copper are home already
+    assert!(is_settled((2, 2)));
+    assert!(is_settled((2, 6)));
+    assert!(!is_settled((1, 6)));
+    assert!(!is_settled((2, 8)));
+    assert!(!board.is_done());
+}
 
-// #[test]
-// fn day_23_part_two() {
-//     assert_eq!(1228699515783640, part_two(fetch_file_path()).unwrap())
-// }
+#[test]
+fn default_board() {
+    let board = Board::default();
 
+    assert_eq!(11 + 4 * 2, board.spots.len());
+    assert!(board.spots.values().all(|x| x.is_none()));
+    assert_eq!(Interior::Room(Amphipod::Desert), board.interior[&(2, 8)]);
+    assert_eq!(
+        BTreeSet::from([Direction::Left, Direction::Right, Direction::Down]),
+        board.connections[&(0, 2)]
+    );
+    assert_eq!(BTreeSet::from([Direction::Up]), board.connections[&(2, 2)]);
+}
+
+#[test]
+fn unfolded_board() {
+    let board = Board::from_text(&unfold(EXAMPLE)).unwrap();
+
+    assert_eq!(11 + 4 * 4, board.spots.len());
+    assert_eq!(Some(Amphipod::Amber), board.spots[&(4, 2)]);
+    assert_eq!(Some(Amphipod::Copper), board.spots[&(3, 8)]);
+    assert_eq!(BTreeSet::from([Direction::Up]), board.connections[&(4, 8)]);
+}
+
+#[test]
+fn custom_layout() {
+    let board = Board::from_text(
+        "#########
+#.......#
+###B#A###
+  #A#B#
+  #####",
+    )
+    .unwrap();
+
+    assert_eq!(7 + 2 * 2, board.spots.len());
+    assert_eq!(Some(46), board.solve());
+
+    // the hallway only has space for one, so someone has to go straight from room to room:
+    // amber steps out, bronze crosses over and amber goes home for 3 + 30 + 2
+    let board = Board::from_text("#####\n#...#\n##BA#\n#####").unwrap();
+    let mut bronze_out = board.clone();
+    bronze_out.apply(&Move {
+        amphipod: Amphipod::Bronze,
+        from: (1, 1),
+        to: (0, 0),
+        energy: 20,
+    });
+    assert_eq!(vec![((1, 2), (1, 1), 3)], bronze_out.moves());
+    assert_eq!(Some(35), board.solve());
+    let topology = Topology::new(&board);
+    assert_eq!(Some(35), topology.solve(topology.unpacked(&board)));
+
+    // too many spots for the packed states, both solve it unpacked
+    let hallway = ".".repeat(state::MAX_SPOTS + 1);
+    let board = Board::from_text(&format!("#####\n#{}#\n##BA#\n#####", hallway)).unwrap();
+    let moves = board.solution().unwrap();
+    assert_eq!(35, moves.iter().map(|x| x.energy).sum::<usize>());
+    assert_eq!(Some(35), board.solve());
+    assert!(Topology::new(&board).state(&board).is_err());
+
+    let mut organized = board.clone();
+    moves.iter().for_each(|x| organized.apply(x));
+    assert!(organized.is_done());
+
+    assert!(Board::from_text("#####\n#...#\n###X#\n").is_err());
+    assert!(Board::from_text("#####\n").is_err());
+}
+
+#[test]
+fn day_23_part_one() {
+    assert_eq!(12521, part_one(fetch_file_path()).unwrap())
+}
+
+#[test]
+fn day_23_part_two() {
+    assert_eq!(44169, part_two(fetch_file_path()).unwrap())
+}
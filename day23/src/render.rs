@@ -0,0 +1,87 @@
+use crate::{Board, Move};
+
+impl Board {
+    /// the burrow as in the puzzle text, every cell next to a spot is a wall
+    pub fn render(&self) -> String {
+        let rows = self.spots.keys().map(|x| x.0).max().unwrap_or(0) + 3;
+        let columns = self.spots.keys().map(|x| x.1).max().unwrap_or(0) + 3;
+        let mut canvas = vec![vec![' '; columns]; rows];
+        for ((row, column), occupant) in self.spots.iter() {
+            canvas[row + 1][column + 1] = match occupant {
+                Some(amphipod) => amphipod.to_string().chars().next().unwrap_or('?'),
+                None => '.',
+            };
+        }
+
+        let next_to_spot = |row: usize, column: usize| {
+            (row.saturating_sub(1)..=row + 1)
+                .flat_map(|x| (column.saturating_sub(1)..=column + 1).map(move |y| (x, y)))
+                .any(|(x, y)| x > 0 && y > 0 && self.spots.contains_key(&(x - 1, y - 1)))
+        };
+        for (row, line) in canvas.iter_mut().enumerate() {
+            for (column, cell) in line.iter_mut().enumerate() {
+                if *cell == ' ' && next_to_spot(row, column) {
+                    *cell = '#';
+                }
+            }
+        }
+
+        canvas
+            .iter()
+            .map(|line| line.iter().collect::<String>().trim_end().to_string() + "\n")
+            .collect()
+    }
+}
+
+/// every burrow along the moves, each one below the move that leads to it
+pub fn render_moves(board: &Board, moves: &[Move]) -> String {
+    let mut board = board.clone();
+    let mut output = board.render();
+    let mut total = 0;
+    for step in moves {
+        board.apply(step);
+        total += step.energy;
+        output.push_str(&format!("\n{}, {} in total\n", step, total));
+        output.push_str(&board.render());
+    }
+    output
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::render_moves;
+    use crate::{fetch_file_path, unfold, Board, EXAMPLE};
+    use std::fs::read_to_string;
+
+    #[test]
+    fn render_diagram() {
+        let board = Board::from_text(EXAMPLE).unwrap();
+        assert_eq!(format!("{}\n", EXAMPLE), board.render());
+
+        let unfolded = Board::from_text(&unfold(EXAMPLE)).unwrap();
+        assert_eq!(unfold(EXAMPLE) + "\n", unfolded.render());
+        assert_eq!(unfolded, Board::from_text(&unfolded.render()).unwrap());
+    }
+
+    #[test]
+    fn solution_moves() {
+        let data = read_to_string(fetch_file_path()).unwrap();
+        let board = Board::from_text(&data).unwrap();
+        let moves = board.solution().unwrap();
+
+        assert_eq!(12521, moves.iter().map(|x| x.energy).sum::<usize>());
+        let mut finished = board.clone();
+        for step in moves.iter() {
+            assert_eq!(Some(step.amphipod), finished.spots[&step.from]);
+            assert!(finished
+                .moves()
+                .contains(&(step.from, step.to, step.energy)));
+            finished.apply(step);
+        }
+        assert!(finished.is_done());
+
+        let output = render_moves(&board, &moves);
+        assert_eq!(moves.len() + 1, output.matches("#############").count());
+        assert!(output.ends_with(&format!("12521 in total\n{}", finished.render())));
+    }
+}
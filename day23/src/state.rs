@@ -0,0 +1,393 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::{Amphipod, Board, Interior, Move, Position, Spots};
+
+/// the state before a move together with `from`, `to` and the energy of the move
+type Previous<S> = Option<(S, usize, usize, usize)>;
+
+const BITS: usize = 3;
+/// amount of spots a packed state has room for
+pub const MAX_SPOTS: usize = 128 / BITS;
+
+/// Occupants of the spots of a `Topology`, by index.
+pub trait Occupancy: Clone + Eq + Ord + Hash {
+    fn get(&self, index: usize) -> Option<Amphipod>;
+
+    /// the state after the occupant of `from` moved to `to`
+    fn moved(&self, from: usize, to: usize) -> Self;
+}
+
+/// A spot per element, for boards with more than `MAX_SPOTS` spots.
+pub type Unpacked = Vec<Option<Amphipod>>;
+
+impl Occupancy for Unpacked {
+    fn get(&self, index: usize) -> Option<Amphipod> {
+        self[index]
+    }
+
+    fn moved(&self, from: usize, to: usize) -> Unpacked {
+        let mut state = self.clone();
+        state[to] = state[from].take();
+        state
+    }
+}
+
+/// Occupants of every spot in three bits each, in the order of the spots in the `Topology`.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct State(u128);
+
+impl Occupancy for State {
+    fn get(&self, index: usize) -> Option<Amphipod> {
+        match (self.0 >> (index * BITS)) & 0b111 {
+            1 => Some(Amphipod::Amber),
+            2 => Some(Amphipod::Bronze),
+            3 => Some(Amphipod::Copper),
+            4 => Some(Amphipod::Desert),
+            _ => None,
+        }
+    }
+
+    fn moved(&self, from: usize, to: usize) -> State {
+        let mut state = *self;
+        state.set(to, self.get(from));
+        state.set(from, None);
+        state
+    }
+}
+
+impl State {
+    pub fn set(&mut self, index: usize, occupant: Option<Amphipod>) {
+        let code: u128 = match occupant {
+            None => 0,
+            Some(Amphipod::Amber) => 1,
+            Some(Amphipod::Bronze) => 2,
+            Some(Amphipod::Copper) => 3,
+            Some(Amphipod::Desert) => 4,
+        };
+        self.0 = (self.0 & !(0b111 << (index * BITS))) | code << (index * BITS);
+    }
+}
+
+/// The parts of a board that never change during the search, spots are referred to by index.
+#[derive(Debug, PartialEq)]
+pub struct Topology {
+    positions: Vec<Position>,
+    interior: Vec<Interior>,
+    /// hallway spots right above a room
+    entrance: Vec<bool>,
+    neighbours: Vec<Vec<usize>>,
+    /// spots of every room from top to bottom
+    rooms: BTreeMap<Amphipod, Vec<usize>>,
+}
+
+impl Topology {
+    pub fn new(board: &Board) -> Topology {
+        let positions: Vec<_> = board.spots.keys().copied().collect();
+        let index: BTreeMap<_, _> = positions.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+        let mut rooms: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (i, position) in positions.iter().enumerate() {
+            if let Interior::Room(amphipod) = board.interior[position] {
+                rooms.entry(amphipod).or_default().push(i);
+            }
+        }
+
+        Topology {
+            interior: positions.iter().map(|x| board.interior[x]).collect(),
+            entrance: positions.iter().map(|x| board.is_entrance(x)).collect(),
+            neighbours: positions
+                .iter()
+                .map(|position| {
+                    board.connections[position]
+                        .iter()
+                        .filter_map(|x| index.get(&x.step(position)?).copied())
+                        .collect()
+                })
+                .collect(),
+            positions,
+            rooms,
+        }
+    }
+
+    pub fn position(&self, index: usize) -> Position {
+        self.positions[index]
+    }
+
+    pub fn index(&self, position: &Position) -> Option<usize> {
+        self.positions.binary_search(position).ok()
+    }
+
+    /// the occupants of the board packed, fails for boards with more than `MAX_SPOTS` spots,
+    /// the board has to have the spots of this topology
+    pub fn state(&self, board: &Board) -> Result<State, Box<dyn std::error::Error>> {
+        if self.positions.len() > MAX_SPOTS {
+            return Err(format!(
+                "a packed state holds {} spots, the board has {}",
+                MAX_SPOTS,
+                self.positions.len()
+            )
+            .into());
+        }
+        let mut state = State::default();
+        for (i, position) in self.positions.iter().enumerate() {
+            state.set(i, board.spots[position]);
+        }
+        Ok(state)
+    }
+
+    /// the occupants of a board of any size
+    pub fn unpacked(&self, board: &Board) -> Unpacked {
+        self.positions.iter().map(|x| board.spots[x]).collect()
+    }
+
+    pub fn spots<S: Occupancy>(&self, state: &S) -> Spots {
+        self.positions
+            .iter()
+            .enumerate()
+            .map(|(i, position)| (*position, state.get(i)))
+            .collect()
+    }
+
+    /// empty spots reachable from a spot with the amount of steps it takes
+    fn reachable<S: Occupancy>(&self, state: &S, from: usize) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let mut seen = vec![false; self.positions.len()];
+        let mut queue = VecDeque::from([(from, 0)]);
+        seen[from] = true;
+        while let Some((current, steps)) = queue.pop_front() {
+            for next in self.neighbours[current].iter().copied() {
+                if !seen[next] && state.get(next).is_none() {
+                    seen[next] = true;
+                    found.push((next, steps + 1));
+                    queue.push_back((next, steps + 1));
+                }
+            }
+        }
+        found
+    }
+
+    fn room(&self, amphipod: Amphipod) -> &[usize] {
+        self.rooms.get(&amphipod).map_or(&[], |x| x.as_slice())
+    }
+
+    /// in its own room without strangers below it
+    pub fn is_settled<S: Occupancy>(&self, state: &S, index: usize) -> bool {
+        match state.get(index) {
+            Some(amphipod) => {
+                let room = self.room(amphipod);
+                room.iter()
+                    .position(|x| *x == index)
+                    .is_some_and(|at| room[at..].iter().all(|x| state.get(*x) == Some(amphipod)))
+            }
+            None => false,
+        }
+    }
+
+    /// the spot an amphipod would take in its room, if the room only holds its own kind
+    fn destination<S: Occupancy>(&self, state: &S, amphipod: Amphipod) -> Option<usize> {
+        let room = self.room(amphipod);
+        if room
+            .iter()
+            .any(|x| state.get(*x).is_some_and(|x| x != amphipod))
+        {
+            return None;
+        }
+        room.iter().rev().copied().find(|x| state.get(*x).is_none())
+    }
+
+    /// all legal moves as `from`, `to` and the energy they take
+    pub fn moves<S: Occupancy>(&self, state: &S) -> Vec<(usize, usize, usize)> {
+        let mut moves = Vec::new();
+        for from in 0..self.positions.len() {
+            let amphipod = match state.get(from) {
+                Some(amphipod) => amphipod,
+                None => continue,
+            };
+            match self.interior[from] {
+                Interior::Hall => {
+                    if let Some(to) = self.destination(state, amphipod) {
+                        if let Some((_, steps)) =
+                            self.reachable(state, from).into_iter().find(|x| x.0 == to)
+                        {
+                            moves.push((from, to, steps * amphipod.energy()));
+                        }
+                    }
+                }
+                Interior::Room(_) if self.is_settled(state, from) => {}
+                Interior::Room(_) => {
+                    // straight into its own room when the way is free, or out into the hallway
+                    let home = self.destination(state, amphipod);
+                    moves.extend(
+                        self.reachable(state, from)
+                            .into_iter()
+                            .filter(|(to, _)| {
+                                Some(*to) == home
+                                    || self.interior[*to] == Interior::Hall && !self.entrance[*to]
+                            })
+                            .map(|(to, steps)| (from, to, steps * amphipod.energy())),
+                    )
+                }
+            }
+        }
+        moves
+    }
+
+    pub fn is_done<S: Occupancy>(&self, state: &S) -> bool {
+        self.interior
+            .iter()
+            .enumerate()
+            .all(|(i, interior)| match (interior, state.get(i)) {
+                (Interior::Hall, None) => true,
+                (Interior::Room(room), Some(amphipod)) => *room == amphipod,
+                _ => false,
+            })
+    }
+
+    /// least energy to organize the amphipods
+    pub fn solve<S: Occupancy>(&self, start: S) -> Option<usize> {
+        self.solution(start)
+            .map(|moves| moves.iter().map(|x| x.energy).sum())
+    }
+
+    /// cheapest moves to organize the amphipods, dijkstra over the states
+    pub fn solution<S: Occupancy>(&self, start: S) -> Option<Vec<Move>> {
+        let mut best: HashMap<S, (usize, Previous<S>)> =
+            HashMap::from([(start.clone(), (0, None))]);
+        let mut queue = BinaryHeap::from([Reverse((0, start))]);
+
+        while let Some(Reverse((energy, state))) = queue.pop() {
+            if best.get(&state).is_some_and(|x| x.0 < energy) {
+                continue;
+            }
+            if self.is_done(&state) {
+                return Some(self.path(&best, state));
+            }
+
+            for (from, to, cost) in self.moves(&state) {
+                let next = state.moved(from, to);
+                let energy = energy + cost;
+                if best.get(&next).is_none_or(|x| energy < x.0) {
+                    best.insert(
+                        next.clone(),
+                        (energy, Some((state.clone(), from, to, cost))),
+                    );
+                    queue.push(Reverse((energy, next)));
+                }
+            }
+        }
+        None
+    }
+
+    /// follows the moves back from the last state to the start
+    fn path<S: Occupancy>(
+        &self,
+        best: &HashMap<S, (usize, Previous<S>)>,
+        mut state: S,
+    ) -> Vec<Move> {
+        let mut moves = Vec::new();
+        while let Some((previous, from, to, energy)) = best[&state].1.clone() {
+            moves.push(Move {
+                amphipod: state.get(to).expect("the amphipod moved there"),
+                from: self.positions[from],
+                to: self.positions[to],
+                energy,
+            });
+            state = previous;
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+#[cfg(test)]
+mod state_tests {
+    use super::{Occupancy, State, Topology, MAX_SPOTS};
+    use crate::{fetch_file_path, unfold, Amphipod, Board};
+    use std::fs::read_to_string;
+    use std::time::Instant;
+
+    fn input_board(unfolded: bool) -> Board {
+        let data = read_to_string(fetch_file_path()).unwrap();
+        if unfolded {
+            Board::from_text(&unfold(&data)).unwrap()
+        } else {
+            Board::from_text(&data).unwrap()
+        }
+    }
+
+    #[test]
+    fn packing() {
+        let mut state = State::default();
+        state.set(0, Some(Amphipod::Desert));
+        state.set(MAX_SPOTS - 1, Some(Amphipod::Amber));
+        state.set(7, Some(Amphipod::Bronze));
+        state.set(7, Some(Amphipod::Copper));
+
+        assert_eq!(Some(Amphipod::Desert), state.get(0));
+        assert_eq!(Some(Amphipod::Amber), state.get(MAX_SPOTS - 1));
+        assert_eq!(Some(Amphipod::Copper), state.get(7));
+        assert_eq!(None, state.get(6));
+
+        let moved = state.moved(7, 8);
+        assert_eq!(None, moved.get(7));
+        assert_eq!(Some(Amphipod::Copper), moved.get(8));
+        assert_ne!(state, moved);
+    }
+
+    #[test]
+    fn packed_same_as_unpacked() {
+        for unfolded in [false, true] {
+            let board = input_board(unfolded);
+            let topology = Topology::new(&board);
+            let state = topology.state(&board).unwrap();
+            let unpacked = topology.unpacked(&board);
+
+            assert_eq!(board.spots, topology.spots(&state));
+            assert_eq!(board.spots, topology.spots(&unpacked));
+            assert_eq!(topology.moves(&unpacked), topology.moves(&state));
+        }
+    }
+
+    #[test]
+    fn too_many_spots() {
+        let hallway = ".".repeat(MAX_SPOTS + 1);
+        let board = Board::from_text(&format!("#\n#{}#\n", hallway)).unwrap();
+        let topology = Topology::new(&board);
+
+        assert!(topology.state(&board).is_err());
+        assert_eq!(MAX_SPOTS + 1, topology.unpacked(&board).len());
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_solvers() {
+        let solvers = [
+            (
+                "unpacked",
+                (|board| {
+                    let topology = Topology::new(board);
+                    topology.solve(topology.unpacked(board))
+                }) as fn(&Board) -> Option<usize>,
+            ),
+            ("packed", |board| {
+                let topology = Topology::new(board);
+                topology.solve(topology.state(board).unwrap())
+            }),
+        ];
+        for unfolded in [false, true] {
+            let board = input_board(unfolded);
+            let energies: Vec<_> = solvers
+                .iter()
+                .map(|(label, solve)| {
+                    let start = Instant::now();
+                    let energy = solve(&board);
+                    println!("{}, unfolded {}: {:?}", label, unfolded, start.elapsed());
+                    energy
+                })
+                .collect();
+
+            assert_eq!(energies[0], energies[1]);
+        }
+    }
+}
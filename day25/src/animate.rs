@@ -0,0 +1,194 @@
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::dense::DenseGrid;
+use crate::Direction;
+
+const EAST_COLOR: (u8, u8, u8) = (255, 140, 0);
+const SOUTH_COLOR: (u8, u8, u8) = (30, 144, 255);
+const EMPTY_COLOR: (u8, u8, u8) = (16, 24, 48);
+
+impl DenseGrid {
+    /// binary PPM with a pixel per cell
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for y in 0..self.height {
+            let mut row = Vec::with_capacity(self.width * 3);
+            for x in 0..self.width {
+                let (r, g, b) = match self.get(&(y, x)) {
+                    Some(Direction::East) => EAST_COLOR,
+                    Some(Direction::South) => SOUTH_COLOR,
+                    None => EMPTY_COLOR,
+                };
+                row.extend_from_slice(&[r, g, b]);
+            }
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Steps a grid until nothing moves and shows every `every`-th state, the start and the
+/// last state are always shown.
+#[derive(Debug)]
+pub struct Animation {
+    grid: DenseGrid,
+    every: usize,
+    max_steps: usize,
+}
+
+impl Animation {
+    pub fn new(grid: DenseGrid, every: usize, max_steps: usize) -> Animation {
+        Animation {
+            grid,
+            every: every.max(1),
+            max_steps,
+        }
+    }
+
+    /// hands the step and the state of every frame to `frame`, returns the amount of frames
+    pub fn run<F>(mut self, mut frame: F) -> std::io::Result<usize>
+    where
+        F: FnMut(usize, &DenseGrid) -> std::io::Result<()>,
+    {
+        frame(0, &self.grid)?;
+        let mut frames = 1;
+        for step in 1..=self.max_steps {
+            let last = self.grid.step() == 0 || step == self.max_steps;
+            if last || step % self.every == 0 {
+                frame(step, &self.grid)?;
+                frames += 1;
+            }
+            if last {
+                break;
+            }
+        }
+        Ok(frames)
+    }
+
+    /// draws the frames over each other, waiting `delay` after each one
+    pub fn to_terminal<W: Write>(self, mut writer: W, delay: Duration) -> std::io::Result<usize> {
+        // hide the cursor and clear the screen once, every frame starts at the top left
+        write!(writer, "\x1b[?25l\x1b[2J")?;
+        let frames = self.run(|step, grid| {
+            writeln!(writer, "\x1b[H{}step {}", grid.print(), step)?;
+            writer.flush()?;
+            sleep(delay);
+            Ok(())
+        });
+        write!(writer, "\x1b[?25h")?;
+        writer.flush()?;
+        frames
+    }
+
+    /// writes the frames as `frame_00000.ppm`, `frame_00001.ppm` and so on
+    pub fn to_ppm_files(self, directory: &Path) -> std::io::Result<usize> {
+        create_dir_all(directory)?;
+        let mut index = 0;
+        self.run(|_, grid| {
+            let file = File::create(directory.join(format!("frame_{:05}.ppm", index)))?;
+            let mut writer = BufWriter::new(file);
+            grid.write_ppm(&mut writer)?;
+            index += 1;
+            writer.flush()
+        })
+    }
+}
+
+#[cfg(test)]
+mod animate_tests {
+    use super::Animation;
+    use crate::dense::DenseGrid;
+    use crate::{Grid, EXAMPLE};
+    use std::env::temp_dir;
+    use std::fs::{read, read_dir, remove_dir_all};
+    use std::io::{BufReader, Cursor};
+    use std::time::Duration;
+
+    fn dense(input: &str) -> DenseGrid {
+        DenseGrid::from(&Grid::from_reader(BufReader::new(Cursor::new(input))).unwrap())
+    }
+
+    #[test]
+    fn frame_steps() {
+        let mut steps = Vec::new();
+        let frames = Animation::new(dense(EXAMPLE), 10, 1000)
+            .run(|step, _| {
+                steps.push(step);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(vec![0, 10, 20, 30, 40, 50, 58], steps);
+        assert_eq!(steps.len(), frames);
+
+        // a cycle never stops, the last frame is the one at the step cap
+        let mut steps = Vec::new();
+        Animation::new(dense(">.\n"), 3, 7)
+            .run(|step, _| {
+                steps.push(step);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(vec![0, 3, 6, 7], steps);
+    }
+
+    #[test]
+    fn terminal() {
+        let mut output = Vec::new();
+        let frames = Animation::new(dense(">.\n"), 1, 2)
+            .to_terminal(&mut output, Duration::ZERO)
+            .unwrap();
+
+        assert_eq!(3, frames);
+        assert_eq!(
+            "\x1b[?25l\x1b[2J\x1b[H>.\nstep 0\n\x1b[H.>\nstep 1\n\x1b[H>.\nstep 2\n\x1b[?25h",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    #[test]
+    fn ppm() {
+        let mut output = Vec::new();
+        dense(">v.\n").write_ppm(&mut output).unwrap();
+
+        let header = b"P6\n3 1\n255\n";
+        assert_eq!(header, &output[..header.len()]);
+        assert_eq!(
+            &[255, 140, 0, 30, 144, 255, 16, 24, 48],
+            &output[header.len()..]
+        );
+    }
+
+    #[test]
+    fn ppm_files() {
+        let directory = temp_dir().join(format!("day25_frames_{}", std::process::id()));
+        let frames = Animation::new(dense(EXAMPLE), 20, 1000)
+            .to_ppm_files(&directory)
+            .unwrap();
+
+        let mut names: Vec<_> = read_dir(&directory)
+            .unwrap()
+            .map(|x| x.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(
+            vec![
+                "frame_00000.ppm",
+                "frame_00001.ppm",
+                "frame_00002.ppm",
+                "frame_00003.ppm"
+            ],
+            names
+        );
+        assert_eq!(4, frames);
+
+        let mut first = Vec::new();
+        dense(EXAMPLE).write_ppm(&mut first).unwrap();
+        assert_eq!(first, read(directory.join("frame_00000.ppm")).unwrap());
+        remove_dir_all(&directory).unwrap();
+    }
+}
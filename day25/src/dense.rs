@@ -0,0 +1,217 @@
+use rayon::prelude::*;
+
+use crate::{Direction, Grid};
+
+const WORD: usize = u64::BITS as usize;
+
+/// Both herds as bitsets, every row takes `words` words and bit `x % 64` of word `x / 64`
+/// is the cell in column `x`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DenseGrid {
+    pub width: usize,
+    pub height: usize,
+    words: usize,
+    east: Vec<u64>,
+    south: Vec<u64>,
+}
+
+/// `next[x]` is `bits[x + 1]`, wrapping around at the width
+fn from_next(bits: &[u64], width: usize, next: &mut [u64]) {
+    for i in 0..bits.len() {
+        let carry = bits.get(i + 1).map_or(0, |x| x << (WORD - 1));
+        next[i] = bits[i] >> 1 | carry;
+    }
+    let last = width - 1;
+    next[last / WORD] |= (bits[0] & 1) << (last % WORD);
+}
+
+/// `previous[x + 1]` is `bits[x]`, wrapping around at the width
+fn from_previous(bits: &[u64], width: usize, previous: &mut [u64]) {
+    for i in 0..bits.len() {
+        let carry = if i > 0 { bits[i - 1] >> (WORD - 1) } else { 0 };
+        previous[i] = bits[i] << 1 | carry;
+    }
+    let last = width - 1;
+    if !width.is_multiple_of(WORD) {
+        previous[width / WORD] &= !(1 << (width % WORD));
+    }
+    previous[0] |= (bits[last / WORD] >> (last % WORD)) & 1;
+}
+
+impl DenseGrid {
+    pub fn get(&self, (y, x): &(usize, usize)) -> Option<Direction> {
+        let (word, bit) = (y * self.words + x / WORD, x % WORD);
+        if self.east[word] >> bit & 1 == 1 {
+            Some(Direction::East)
+        } else if self.south[word] >> bit & 1 == 1 {
+            Some(Direction::South)
+        } else {
+            None
+        }
+    }
+
+    fn east_step(&mut self) -> usize {
+        let (width, words) = (self.width, self.words);
+        self.east
+            .par_chunks_mut(words)
+            .zip(self.south.par_chunks(words))
+            .map(|(east, south)| {
+                let occupied: Vec<_> = east.iter().zip(south).map(|(a, b)| a | b).collect();
+                let mut next = vec![0; words];
+                from_next(&occupied, width, &mut next);
+                let movers: Vec<_> = east.iter().zip(&next).map(|(a, b)| a & !b).collect();
+
+                let mut arrivals = vec![0; words];
+                from_previous(&movers, width, &mut arrivals);
+                for i in 0..words {
+                    east[i] = east[i] & !movers[i] | arrivals[i];
+                }
+                movers
+                    .iter()
+                    .map(|x| x.count_ones() as usize)
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+
+    fn south_step(&mut self) -> usize {
+        let (height, words) = (self.height, self.words);
+        let (east, south) = (&self.east, &self.south);
+        let mut movers = vec![0; south.len()];
+        movers
+            .par_chunks_mut(words)
+            .enumerate()
+            .for_each(|(y, movers)| {
+                let below = (y + 1) % height * words;
+                for i in 0..words {
+                    movers[i] = south[y * words + i] & !(east[below + i] | south[below + i]);
+                }
+            });
+
+        self.south
+            .par_chunks_mut(words)
+            .enumerate()
+            .for_each(|(y, south)| {
+                let above = (y + height - 1) % height * words;
+                for i in 0..words {
+                    south[i] = south[i] & !movers[y * words + i] | movers[above + i];
+                }
+            });
+        movers.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    /// moves the east herd and then the south herd, returns how many sea cucumbers moved
+    pub fn step(&mut self) -> usize {
+        if self.width == 0 || self.height == 0 {
+            return 0;
+        }
+        self.east_step() + self.south_step()
+    }
+
+    pub fn print(&self) -> String {
+        let mut buffer = String::new();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                let ch = match self.get(&(i, j)) {
+                    Some(Direction::South) => 'v',
+                    Some(Direction::East) => '>',
+                    None => '.',
+                };
+                buffer.push(ch)
+            }
+            buffer.push('\n')
+        }
+        buffer
+    }
+}
+
+impl From<&Grid> for DenseGrid {
+    fn from(grid: &Grid) -> DenseGrid {
+        let words = grid.width.div_ceil(WORD);
+        let mut dense = DenseGrid {
+            width: grid.width,
+            height: grid.height,
+            words,
+            east: vec![0; words * grid.height],
+            south: vec![0; words * grid.height],
+        };
+        for ((y, x), direction) in grid.field.iter() {
+            let herd = match direction {
+                Direction::East => &mut dense.east,
+                Direction::South => &mut dense.south,
+            };
+            herd[y * words + x / WORD] |= 1 << (x % WORD);
+        }
+        dense
+    }
+}
+
+#[cfg(test)]
+mod dense_tests {
+    use super::DenseGrid;
+    use crate::{fetch_file_path, Grid, EXAMPLE};
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+    use std::time::Instant;
+
+    fn grid(input: &str) -> Grid {
+        Grid::from_reader(BufReader::new(Cursor::new(input))).unwrap()
+    }
+
+    #[test]
+    fn matches_map_grid() {
+        let mut grid = grid(EXAMPLE);
+        let mut dense = DenseGrid::from(&grid);
+        assert_eq!(EXAMPLE, dense.print());
+
+        for _ in 0..60 {
+            assert_eq!(grid.step(), dense.step());
+            assert_eq!(grid.print(), dense.print());
+        }
+    }
+
+    #[test]
+    fn example_stops_after_58_steps() {
+        let mut dense = DenseGrid::from(&grid(EXAMPLE));
+        let steps = (1..).find(|_| dense.step() == 0).unwrap();
+
+        assert_eq!(58, steps);
+    }
+
+    #[test]
+    fn wraps_around_words() {
+        // 64 and 65 wide rows put the wrapping cucumber on a word boundary
+        for width in [63, 64, 65, 130] {
+            let line = ".".repeat(width - 1) + ">\n";
+            let column = "v".to_string() + &".".repeat(width - 1) + "\n";
+            let input = line.clone() + &column + &line;
+            let mut grid = grid(&input);
+            let mut dense = DenseGrid::from(&grid);
+
+            for _ in 0..4 {
+                assert_eq!(grid.step(), dense.step(), "width {}", width);
+                assert_eq!(grid.print(), dense.print(), "width {}", width);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_map_vs_dense() {
+        let mut map =
+            Grid::from_reader(BufReader::new(File::open(fetch_file_path()).unwrap())).unwrap();
+        let mut dense = DenseGrid::from(&map);
+
+        // both step until nothing moves
+        let grids: [(&str, &mut dyn FnMut() -> usize); 2] =
+            [("map", &mut || map.step()), ("dense", &mut || dense.step())];
+        let mut steps = Vec::new();
+        for (label, step) in grids {
+            let start = Instant::now();
+            steps.push((1..).find(|_| step() == 0).unwrap());
+            println!("{}: {:?}", label, start.elapsed());
+        }
+
+        assert_eq!(steps[0], steps[1]);
+    }
+}
@@ -3,6 +3,13 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
+pub mod animate;
+pub mod dense;
+pub mod simulate;
+
+use dense::DenseGrid;
+use simulate::{Outcome, Simulator};
+
 #[derive(Debug)]
 pub enum Direction {
     East,
@@ -95,6 +102,17 @@ impl Grid {
         }
     }
 
+    /// moves the east herd and then the south herd, returns how many sea cucumbers moved
+    pub fn step(&mut self) -> usize {
+        let positions: Vec<_> = self.move_east_iter().collect();
+        self.update_step(&positions);
+        let moved = positions.len();
+
+        let positions: Vec<_> = self.move_south_iter().collect();
+        self.update_step(&positions);
+        moved + positions.len()
+    }
+
     pub fn move_south_iter<'a>(
         &'a self,
     ) -> Box<dyn Iterator<Item = ((usize, usize), (usize, usize))> + 'a> {
@@ -182,30 +200,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 fn part_one(input_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
     let file = File::open(input_path)?;
     let reader = BufReader::new(file);
-    let mut grid = Grid::from_reader(reader)?;
-
-    let mut counter = 0;
-    loop {
-        counter += 1;
-        let mut no_movement;
-        let positions: Vec<_> = grid.move_east_iter().collect();
-        grid.update_step(&positions);
-        no_movement = positions.is_empty();
-
-        let positions: Vec<_> = grid.move_south_iter().collect();
-        grid.update_step(&positions);
-        no_movement &= positions.is_empty();
-
-        if no_movement {
-            break;
+    let grid = DenseGrid::from(&Grid::from_reader(reader)?);
+
+    match Simulator::new(grid, 10_000).run()? {
+        Outcome::FixedPoint { step } => Ok(step),
+        Outcome::Cycle { period, .. } => {
+            Err(format!("the herds never stop, they repeat every {} steps", period).into())
         }
     }
-
-    Ok(counter)
 }
 
-// only fast enough if you run in release mode
-#[cfg(not(debug_assertions))]
+/// the example herds, they stop after 58 steps
+#[cfg(test)]
+const EXAMPLE: &str = "\
+v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>
+";
+
 #[test]
 fn day_25_part_one() {
     assert_eq!(321, part_one(fetch_file_path()).unwrap())
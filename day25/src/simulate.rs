@@ -0,0 +1,143 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::dense::DenseGrid;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    /// `step` is the first step in which no sea cucumber moved
+    FixedPoint { step: usize },
+    /// the state after `first_repeat` steps was already seen `period` steps earlier
+    Cycle { first_repeat: usize, period: usize },
+}
+
+/// Steps a grid and remembers every state it passed, to notice when the herds stop or repeat.
+#[derive(Debug)]
+pub struct Simulator {
+    /// the state after every step, the first one is the start
+    history: Vec<DenseGrid>,
+    /// positions in the history by the hash of the state
+    seen: HashMap<u64, Vec<usize>>,
+    max_steps: usize,
+}
+
+impl Simulator {
+    pub fn new(grid: DenseGrid, max_steps: usize) -> Simulator {
+        let mut simulator = Simulator {
+            history: Vec::new(),
+            seen: HashMap::new(),
+            max_steps,
+        };
+        simulator.remember(grid);
+        simulator
+    }
+
+    fn hash(grid: &DenseGrid) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        grid.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// step at which the state was seen before, if it was
+    fn remember(&mut self, grid: DenseGrid) -> Option<usize> {
+        let positions = self.seen.entry(Self::hash(&grid)).or_default();
+        let before = positions.iter().copied().find(|x| self.history[*x] == grid);
+        positions.push(self.history.len());
+        self.history.push(grid);
+        before
+    }
+
+    /// amount of steps taken
+    pub fn steps(&self) -> usize {
+        self.history.len() - 1
+    }
+
+    /// the state after the given amount of steps
+    pub fn state(&self, step: usize) -> Option<&DenseGrid> {
+        self.history.get(step)
+    }
+
+    pub fn grid(&self) -> &DenseGrid {
+        self.history
+            .last()
+            .expect("the start is always in the history")
+    }
+
+    /// steps until nothing moves or a state repeats, at most `max_steps` in total
+    pub fn run(&mut self) -> Result<Outcome, Box<dyn std::error::Error>> {
+        while self.steps() < self.max_steps {
+            let mut grid = self.grid().clone();
+            let moved = grid.step();
+            let before = self.remember(grid);
+
+            if moved == 0 {
+                return Ok(Outcome::FixedPoint { step: self.steps() });
+            }
+            if let Some(before) = before {
+                return Ok(Outcome::Cycle {
+                    first_repeat: self.steps(),
+                    period: self.steps() - before,
+                });
+            }
+        }
+        Err(format!("no fixed point or cycle within {} steps", self.max_steps).into())
+    }
+}
+
+#[cfg(test)]
+mod simulate_tests {
+    use super::{Outcome, Simulator};
+    use crate::dense::DenseGrid;
+    use crate::{fetch_file_path, Grid};
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    fn from_text(input: &str, max_steps: usize) -> Simulator {
+        let grid = Grid::from_reader(BufReader::new(Cursor::new(input))).unwrap();
+        Simulator::new(DenseGrid::from(&grid), max_steps)
+    }
+
+    #[test]
+    fn fixed_point() {
+        let grid =
+            Grid::from_reader(BufReader::new(File::open(fetch_file_path()).unwrap())).unwrap();
+        let mut simulator = Simulator::new(DenseGrid::from(&grid), 1000);
+
+        assert_eq!(Outcome::FixedPoint { step: 321 }, simulator.run().unwrap());
+        assert_eq!(321, simulator.steps());
+        assert_eq!(simulator.state(320), Some(simulator.grid()));
+    }
+
+    #[test]
+    fn cycles() {
+        let mut simulator = from_text(">.\n", 100);
+        assert_eq!(
+            Outcome::Cycle {
+                first_repeat: 2,
+                period: 2
+            },
+            simulator.run().unwrap()
+        );
+
+        // the south cucumber gets stuck behind the east one once, so the start is not repeated
+        let mut simulator = from_text("...>\nv...\n....\n", 100);
+        assert_eq!(
+            Outcome::Cycle {
+                first_repeat: 5,
+                period: 4
+            },
+            simulator.run().unwrap()
+        );
+        assert_eq!(simulator.state(1), simulator.state(5));
+        assert_ne!(simulator.state(0), simulator.state(4));
+    }
+
+    #[test]
+    fn step_cap() {
+        let mut simulator = from_text("...>\nv...\n....\n", 4);
+
+        assert!(simulator.run().is_err());
+        assert_eq!(4, simulator.steps());
+    }
+}